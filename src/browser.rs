@@ -0,0 +1,38 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::progress::log;
+
+/// Opens `path` with the platform's default handler, reporting failures
+/// without aborting.
+pub fn open_report(path: &str) {
+    if let Err(e) = open_path(Path::new(path)) {
+        log!("Error opening {} in the browser: {}", path, e);
+    }
+}
+
+fn open_path(path: &Path) -> io::Result<()> {
+    let path = std::path::absolute(path)?;
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // `start` is a cmd builtin; the empty string is the window title
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            let program = command.get_program().to_string_lossy();
+            io::Error::new(e.kind(), format!("{}: {}", program, e))
+        })?;
+    Ok(())
+}
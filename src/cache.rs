@@ -0,0 +1,142 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, TesterConfig};
+use crate::features::parse_features;
+use crate::results::{Result, Verdict};
+
+/// Results of earlier runs keyed on the solver and the input, so unchanged
+/// seeds need not be run again. Each entry is a `<key>.json` result plus the
+/// solver output in `<key>.out`.
+pub struct ResultCache {
+    dir: PathBuf,
+    solver_hash: String,
+}
+
+impl ResultCache {
+    pub fn new(dir: &str, config: &Config) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(ResultCache {
+            dir: PathBuf::from(dir),
+            solver_hash: solver_hash(config)?,
+        })
+    }
+
+    /// Returns the cached result for `input_file`, restoring its solver output
+    /// to `output_file` so the visualizer can use it.
+    pub fn get(&self, input_file: &str, output_file: &str, config: &Config) -> Option<Result> {
+        let input = fs::read(input_file).ok()?;
+        let key = self.key(&input);
+        let json = fs::read_to_string(self.dir.join(format!("{}.json", key))).ok()?;
+        let mut result: Result = serde_json::from_str(&json).ok()?;
+        fs::copy(self.dir.join(format!("{}.out", key)), output_file).ok()?;
+
+        result.input_file = input_file.to_string();
+        result.output_file = output_file.to_string();
+        result.visualizer = String::new();
+        result.thumbnail = String::new();
+        // Logs are not cached, so they would belong to another run
+        result.stderr_file = String::new();
+        result.solver_stderr_file = String::new();
+        result.case_file = String::new();
+        // Features are cheap to read and may have been configured since
+        result.features = config
+            .features
+            .as_ref()
+            .map(|f| parse_features(&String::from_utf8_lossy(&input), f))
+            .unwrap_or_default();
+        Some(result)
+    }
+
+    /// Stores `result` if it was accepted. Failures are not cached since they
+    /// may be transient, a crash or a timeout under load, and a cached one
+    /// would never be retried.
+    pub fn put(&self, result: &Result) -> io::Result<()> {
+        if result.verdict != Verdict::Ac {
+            return Ok(());
+        }
+        let key = self.key(&fs::read(&result.input_file)?);
+        let output = fs::read(&result.output_file).unwrap_or_default();
+        fs::write(self.dir.join(format!("{}.out", key)), output)?;
+        let json = serde_json::to_string(result)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(self.dir.join(format!("{}.json", key)), json)
+    }
+
+    fn key(&self, input: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.solver_hash.as_bytes());
+        hasher.update(input);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Hashes everything that decides a result besides the input: the tester,
+/// scorer and limit settings, the solver parameters, and the contents of the script and solver
+/// script (every file under them, for directories) and of the files the tester commands name,
+/// such as `./a.out`. Fails when none of those exist, as a rebuilt solver would then go
+/// unnoticed.
+fn solver_hash(config: &Config) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    // The seed selection does not change any single result
+    let tester = TesterConfig {
+        seeds: None,
+        ..config.tester.clone()
+    };
+    hasher.update(toml::to_string(&tester).unwrap_or_default());
+    hasher.update(serde_json::to_string(&config.param_values()).unwrap_or_default());
+    if let Some(ref scorer) = config.scorer {
+        hasher.update(toml::to_string(scorer).unwrap_or_default());
+    }
+    let mut solver_files: Vec<PathBuf> = [&config.tester.script, &config.tester.solver_script]
+        .into_iter()
+        .flatten()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .collect();
+    // Placeholders are left in, so they match no file
+    for command in [
+        Some(config.tester.command.as_str()),
+        config.tester.interactive_tester(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        let words = shell_words::split(command).unwrap_or_default();
+        solver_files.extend(
+            words
+                .iter()
+                .map(PathBuf::from)
+                .filter(|path| path.is_file()),
+        );
+    }
+    if solver_files.is_empty() {
+        return Err(io::Error::other(
+            "no solver file to tell rebuilds apart; set tester.script or name the binary in \
+             tester.command",
+        ));
+    }
+    for path in &solver_files {
+        hash_path(&mut hasher, path)?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_path(hasher: &mut Sha256, path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| !p.ends_with("target") && !p.ends_with(".git"))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            hash_path(hasher, &entry)?;
+        }
+    } else if path.is_file() {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(fs::read(path)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! Inline SVG charts embedded in the HTML report.
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN: f64 = 40.0;
+
+/// How values are laid out along a chart's value axis.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    Linear,
+    /// By `log10(value)`; values of 0 or less are left out
+    Log,
+}
+
+impl Scale {
+    /// Position of `value` on the axis, if it can be shown.
+    fn position(self, value: f64) -> Option<f64> {
+        match self {
+            Scale::Linear => Some(value),
+            Scale::Log => (value > 0.0).then(|| value.log10()),
+        }
+    }
+
+    /// The value at `position`, as an axis label.
+    fn label(self, position: f64) -> String {
+        match self {
+            Scale::Linear => format!("{:.0}", position),
+            Scale::Log => format!("{:.2e}", 10f64.powf(position)),
+        }
+    }
+}
+
+/// Histogram of `values` with roughly sqrt(n) bins, at most 30.
+pub fn histogram_svg(values: &[f64], scale: Scale) -> Option<String> {
+    let values: Vec<f64> = values.iter().filter_map(|&v| scale.position(v)).collect();
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let bins = ((values.len() as f64).sqrt().ceil() as usize).clamp(1, 30);
+    let width = if max > min {
+        (max - min) / bins as f64
+    } else {
+        1.0
+    };
+
+    let mut counts = vec![0usize; bins];
+    for &v in &values {
+        let bin = (((v - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    let max_count = counts.iter().copied().max().unwrap_or(1).max(1);
+
+    let plot_w = WIDTH - 2.0 * MARGIN;
+    let plot_h = HEIGHT - 2.0 * MARGIN;
+    let bar_w = plot_w / bins as f64;
+
+    let mut svg = svg_open();
+    for (i, &count) in counts.iter().enumerate() {
+        let h = plot_h * count as f64 / max_count as f64;
+        let lo = min + width * i as f64;
+        svg.push_str(&format!(
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="steelblue"><title>[{}, {}): {}</title></rect>"#,
+            MARGIN + bar_w * i as f64 + 1.0,
+            MARGIN + plot_h - h,
+            (bar_w - 2.0).max(1.0),
+            h,
+            scale.label(lo),
+            scale.label(lo + width),
+            count
+        ));
+    }
+    svg.push_str(&axes(
+        &scale.label(min),
+        &scale.label(max),
+        "0",
+        &max_count.to_string(),
+    ));
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+/// Scatter plot of `(x, y)` points, with `x_label` under the x axis and
+/// y laid out by `scale`.
+pub fn scatter_svg(points: &[(f64, f64)], x_label: &str, scale: Scale) -> Option<String> {
+    let points: Vec<(f64, f64, f64)> = points
+        .iter()
+        .filter_map(|&(x, y)| Some((x, y, scale.position(y)?)))
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    let (x_min, x_max) = bounds(points.iter().map(|p| p.0));
+    let (y_min, y_max) = bounds(points.iter().map(|p| p.2));
+    let plot_w = WIDTH - 2.0 * MARGIN;
+    let plot_h = HEIGHT - 2.0 * MARGIN;
+    let fraction = |v: f64, lo: f64, hi: f64| if hi > lo { (v - lo) / (hi - lo) } else { 0.5 };
+
+    let mut svg = svg_open();
+    for &(x, y, y_position) in &points {
+        svg.push_str(&format!(
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="steelblue" fill-opacity="0.7"><title>{}: {}</title></circle>"#,
+            MARGIN + plot_w * fraction(x, x_min, x_max),
+            MARGIN + plot_h * (1.0 - fraction(y_position, y_min, y_max)),
+            x,
+            y
+        ));
+    }
+    svg.push_str(&axes(
+        &format!("{:.0}", x_min),
+        &format!("{:.0}", x_max),
+        &scale.label(y_min),
+        &scale.label(y_max),
+    ));
+    svg.push_str(&format!(
+        r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+        WIDTH / 2.0,
+        HEIGHT - MARGIN + 16.0,
+        x_label
+    ));
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+/// Line chart of values in order; each point is `(tooltip, value, link)`,
+/// an empty link leaving the point unlinked.
+pub fn trend_svg(points: &[(String, f64, String)]) -> Option<String> {
+    if points.is_empty() {
+        return None;
+    }
+    let (y_min, y_max) = bounds(points.iter().map(|p| p.1));
+    let plot_w = WIDTH - 2.0 * MARGIN;
+    let plot_h = HEIGHT - 2.0 * MARGIN;
+    let step = if points.len() > 1 {
+        plot_w / (points.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let coords: Vec<(f64, f64)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let t = if y_max > y_min {
+                (p.1 - y_min) / (y_max - y_min)
+            } else {
+                0.5
+            };
+            (MARGIN + step * i as f64, MARGIN + plot_h * (1.0 - t))
+        })
+        .collect();
+
+    let mut svg = svg_open();
+    let path: Vec<String> = coords
+        .iter()
+        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+        .collect();
+    svg.push_str(&format!(
+        r#"<polyline points="{}" fill="none" stroke="steelblue"/>"#,
+        path.join(" ")
+    ));
+    for ((tooltip, _, href), (x, y)) in points.iter().zip(&coords) {
+        let circle = format!(
+            r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="steelblue"><title>{}</title></circle>"#,
+            x, y, tooltip
+        );
+        if href.is_empty() {
+            svg.push_str(&circle);
+        } else {
+            svg.push_str(&format!(r#"<a href="{}">{}</a>"#, href, circle));
+        }
+    }
+    svg.push_str(&axes(
+        "first",
+        "latest",
+        &format!("{:.0}", y_min),
+        &format!("{:.0}", y_max),
+    ));
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    })
+}
+
+fn svg_open() -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+        w = WIDTH,
+        h = HEIGHT
+    )
+}
+
+/// Axis lines with labels at the ends of both axes.
+fn axes(x_min: &str, x_max: &str, y_min: &str, y_max: &str) -> String {
+    let bottom = HEIGHT - MARGIN;
+    let right = WIDTH - MARGIN;
+    format!(
+        concat!(
+            r#"<line x1="{m}" y1="{b}" x2="{r}" y2="{b}" stroke="black"/>"#,
+            r#"<line x1="{m}" y1="{m}" x2="{m}" y2="{b}" stroke="black"/>"#,
+            r#"<text x="{m}" y="{xl}" text-anchor="start">{x_min}</text>"#,
+            r#"<text x="{r}" y="{xl}" text-anchor="end">{x_max}</text>"#,
+            r#"<text x="{yl}" y="{m}" text-anchor="end">{y_max}</text>"#,
+            r#"<text x="{yl}" y="{b}" text-anchor="end">{y_min}</text>"#
+        ),
+        m = MARGIN,
+        b = bottom,
+        r = right,
+        xl = bottom + 16.0,
+        yl = MARGIN - 4.0,
+        x_min = x_min,
+        x_max = x_max,
+        y_min = y_min,
+        y_max = y_max
+    )
+}
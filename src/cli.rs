@@ -0,0 +1,316 @@
+use clap::{ArgGroup, Args, Parser, Subcommand};
+
+use crate::scoring::Objective;
+use crate::seeds::SeedFilter;
+
+#[derive(Parser)]
+#[command(
+    name = "score_visualizer",
+    version,
+    about = "Run a solver over local AHC inputs and build an HTML score report"
+)]
+pub struct Cli {
+    /// Path to the config file (.toml, or .json/.yaml/.yml with the same structure)
+    #[arg(long, global = true, default_value = "./config.toml")]
+    pub config: String,
+
+    /// Apply the values of [profile.NAME] in the config
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Override a config value for this invocation, e.g. --set
+    /// parallel.num_threads=4; repeatable
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_param)]
+    pub overrides: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Run the solver on every input, visualize and write the report (default)
+    Run(RunArgs),
+    /// Write a config.toml (or --config) for the AHC tools found nearby
+    Init(InitArgs),
+    /// Regenerate the HTML report from the cached results of the last run
+    Report(ReportArgs),
+    /// Compare two result files seed by seed
+    Compare(CompareArgs),
+    /// Total of the best answer per seed across recorded runs or result files
+    Ensemble(EnsembleArgs),
+    /// List the recorded runs with their totals, comments and tags
+    History(HistoryArgs),
+    /// Check the config, directories, commands and inputs before a run
+    Doctor,
+    /// Remove generated outputs, visualizations and reports
+    Clean,
+    /// Zip a recorded run's report, results, outputs and visualizations
+    Archive(ArchiveArgs),
+    /// Delete the outputs, visualizations, logs and archived reports of old
+    /// runs, keeping the history and best answers
+    Prune(PruneArgs),
+    /// Rerun only the visualizer over the last run's outputs and rewrite the report
+    Vis,
+    /// Run the seeds once per [sweep] parameter set: every combination of the
+    /// values, or random draws
+    Sweep(SweepArgs),
+    /// Tune the [sweep] parameters by hill climbing, keeping a trial history
+    Optimize(OptimizeArgs),
+    /// Rerun whenever the solver changes
+    Watch(WatchArgs),
+    /// Serve the report over HTTP, optionally streaming a run live
+    Serve(ServeArgs),
+}
+
+#[derive(Args, Default)]
+pub struct RunArgs {
+    /// Result JSON to diff against (default: the last run in the history)
+    #[arg(long)]
+    pub baseline: Option<String>,
+
+    /// Only run these seeds, e.g. 0-49,100,151 (default: tester.seeds, or all)
+    #[arg(long)]
+    pub seeds: Option<SeedFilter>,
+
+    /// Run only N randomly picked seeds and extrapolate the total
+    #[arg(long, visible_alias = "sample", value_name = "N")]
+    pub quick: Option<usize>,
+
+    /// With --quick, sample evenly across K ranges of features.group_by
+    /// (or the first feature)
+    #[arg(long, value_name = "K", requires = "quick")]
+    pub stratify: Option<usize>,
+
+    /// Random seed for --quick, to pick the same seeds every time
+    #[arg(long, value_name = "SEED", requires = "quick")]
+    pub rng_seed: Option<u64>,
+
+    /// Rerun only the seeds that were not AC in the last run, reusing the
+    /// other scores in the report
+    #[arg(long)]
+    pub only_failed: bool,
+
+    /// Rerun only the seeds where the last run was worse than the run before
+    /// it (or --baseline), reusing the other scores in the report
+    #[arg(long)]
+    pub only_regressed: bool,
+
+    /// Stop after the first failed seed, or after run.max_failures of them
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Stop once the seeds so far score significantly worse than the
+    /// baseline: a paired signed-rank test with p below P (default 0.01),
+    /// checked from the 10th seed on. Writes a partial report and fails
+    #[arg(long, value_name = "P", num_args = 0..=1, default_missing_value = "0.01")]
+    pub early_stop: Option<f64>,
+
+    /// Skip visualization (same as visualizer.enabled = false)
+    #[arg(long)]
+    pub no_vis: bool,
+
+    /// Run every seed even if paths.cache_dir has a result for it
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Show a live table of results instead of progress bars
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Rewrite the HTML report every N finished cases while the run is going
+    #[arg(long, value_name = "N")]
+    pub update_every: Option<usize>,
+
+    /// Open the report in the default browser when done
+    #[arg(long)]
+    pub open: bool,
+
+    /// Set a solver parameter for this run, overriding [params]; repeatable
+    #[arg(short = 'p', long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+
+    /// Describe the run, e.g. -m "SA with restarts"; recorded in the history
+    /// and shown in the report
+    #[arg(short = 'm', long = "message", value_name = "TEXT")]
+    pub comment: Option<String>,
+
+    /// Tag the run in the history, e.g. --tag sa; repeatable
+    #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+    pub tags: Vec<String>,
+
+    /// Append a markdown summary to PATH, or to $GITHUB_STEP_SUMMARY (which
+    /// is also used without this flag when set)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+    pub gha_summary: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SweepArgs {
+    /// Only run these seeds, e.g. 0-49,100,151 (default: tester.seeds, or all)
+    #[arg(long)]
+    pub seeds: Option<SeedFilter>,
+
+    /// Run every combination on the same N randomly picked seeds
+    #[arg(long, visible_alias = "sample", value_name = "N")]
+    pub quick: Option<usize>,
+
+    /// Random seed for --quick and the "random" strategy, to pick the same
+    /// seeds and parameters every time
+    #[arg(long, value_name = "SEED")]
+    pub rng_seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct OptimizeArgs {
+    /// Trials to run this session (default: sweep.trials)
+    #[arg(long, value_name = "N")]
+    pub trials: Option<usize>,
+
+    #[command(flatten)]
+    pub sweep: SweepArgs,
+}
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// File or directory to watch (default: tester.script and tester.solver_script)
+    #[arg(long = "path")]
+    pub paths: Vec<String>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Port to listen on (localhost only)
+    #[arg(long, default_value_t = 8000)]
+    pub port: u16,
+
+    /// Start a run and push each result to the live dashboard as it completes
+    #[arg(long)]
+    pub run: bool,
+
+    #[command(flatten)]
+    pub run_args: RunArgs,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Result JSON to diff against
+    #[arg(long)]
+    pub baseline: Option<String>,
+
+    /// Open the report in the default browser when done
+    #[arg(long)]
+    pub open: bool,
+}
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// Result file of the reference run
+    pub base: String,
+    /// Result file of the run to compare against the reference
+    pub target: String,
+    /// Also write the comparison as an HTML page to this path
+    #[arg(long)]
+    pub html: Option<String>,
+    /// Scores from another harness to show as an extra column: a
+    /// `seed,score` CSV or a JSON array of {"seed", "score"} objects
+    /// (repeatable)
+    #[arg(long = "extra", value_name = "FILE")]
+    pub extras: Vec<String>,
+    /// Count seeds whose score changed by at most this percent as ties
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    pub tie_tolerance: f64,
+    /// Whether higher or lower scores win (default: as recorded in the
+    /// target file; pahcer files do not record it)
+    #[arg(long)]
+    pub objective: Option<Objective>,
+}
+
+#[derive(Args)]
+pub struct EnsembleArgs {
+    /// Result files to pick from (default: every run in the history)
+    pub files: Vec<String>,
+    /// Only use the most recent N runs of the history
+    #[arg(long, value_name = "N", conflicts_with = "files")]
+    pub last: Option<usize>,
+    /// Also write the ensemble as an HTML page to this path
+    #[arg(long)]
+    pub html: Option<String>,
+    /// Whether higher or lower scores win (default: from the config, or as
+    /// recorded in the first file)
+    #[arg(long)]
+    pub objective: Option<Objective>,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Directory of the official tools, holding in/ (default: ./tools or
+    /// ../tools, whichever exists)
+    #[arg(long, value_name = "DIR")]
+    pub tools: Option<String>,
+    /// Command running the solver, e.g. "./a.out" or "python3 main.py"
+    #[arg(long)]
+    pub command: Option<String>,
+    /// Whether higher or lower scores win
+    #[arg(long)]
+    pub objective: Option<Objective>,
+    /// Do not ask; take the detected or default value for everything not given
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Overwrite an existing config
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ArchiveArgs {
+    /// Id of the run in the history (default: the latest run)
+    #[arg(long, value_name = "ID")]
+    pub run: Option<i64>,
+    /// Zip file to write (default: run_<id>.zip next to html_output)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("age").required(true).multiple(true)))]
+pub struct PruneArgs {
+    /// Prune runs older than this many days
+    #[arg(long, value_name = "DAYS", group = "age")]
+    pub older_than: Option<u64>,
+    /// Prune all but the most recent K runs
+    #[arg(long, value_name = "K", group = "age")]
+    pub keep_last: Option<usize>,
+    /// List the files that would be removed without removing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    /// Only list the most recent N runs
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+    /// Only list runs with this tag; repeatable, every tag must match
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+}
+
+fn parse_tag(s: &str) -> Result<String, String> {
+    // Tags are stored comma-separated in the history
+    if s.is_empty() || s.contains(',') || s.contains(char::is_whitespace) {
+        Err(format!("a tag is one word without commas, got `{}`", s))
+    } else {
+        Ok(s.to_string())
+    }
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got `{}`", s)),
+    }
+}
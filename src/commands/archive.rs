@@ -0,0 +1,125 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::cli::ArchiveArgs;
+use crate::config::Config;
+use crate::history::{finished_at, history_path, History};
+use crate::html::archived_report_path;
+use crate::results::{load_results, results_path, RunResults};
+use crate::runner::output_path;
+
+pub fn archive(config: &Config, args: &ArchiveArgs) {
+    let run = match load_run(config, args.run) {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            eprintln!("No recorded runs in {}", history_path(config));
+            return;
+        }
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            return;
+        }
+    };
+    let run_id = run.run_id.unwrap_or_default();
+    let output = args.output.clone().unwrap_or_else(|| {
+        Path::new(&config.paths.html_output)
+            .with_file_name(format!("run_{}.zip", run_id))
+            .to_string_lossy()
+            .to_string()
+    });
+
+    match write_archive(config, &run, Path::new(&output)) {
+        Ok(stale) => {
+            if stale > 0 {
+                eprintln!(
+                    "Left out {} files rewritten by later runs; archive a run right after it \
+                     finishes to keep all of them",
+                    stale
+                );
+            }
+            println!("Run #{} archived to {}", run_id, output);
+        }
+        Err(e) => eprintln!("Error writing {}: {}", output, e),
+    }
+}
+
+/// The run with `run_id`, or the latest one. Taken from the results JSON
+/// when that still holds it, since the history keeps no logs or metrics.
+fn load_run(config: &Config, run_id: Option<i64>) -> rusqlite::Result<Option<RunResults>> {
+    let history = History::open(Path::new(history_path(config)))?;
+    let run_id = match run_id {
+        Some(run_id) => run_id,
+        None => match history.run_summaries()?.last() {
+            Some(run) => run.id,
+            None => return Ok(None),
+        },
+    };
+    if let Ok(run) = load_results(&results_path(config)) {
+        if run.run_id == Some(run_id) {
+            return Ok(Some(run));
+        }
+    }
+    history.load_run(run_id).map(Some)
+}
+
+/// Zips the report, results, outputs and visualizations of `run`. Outputs
+/// and visualizations are shared by all runs, so those modified after the
+/// run finished are left out; returns how many.
+fn write_archive(config: &Config, run: &RunResults, path: &Path) -> io::Result<usize> {
+    let finished = finished_at(&run.timestamp).map(|t| t + Duration::from_secs(1));
+    let report = run
+        .run_id
+        .map(|run_id| archived_report_path(config, run_id))
+        .filter(|report| report.is_file());
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for result in &run.results {
+        let output = PathBuf::from(output_path(&config.paths.output_dir, &result.input_file));
+        let vis =
+            output_path(&config.paths.visualizer_dir, &result.input_file).replace(".txt", ".html");
+        let thumbnail = Path::new(&vis).with_extension("png");
+        for (dir, file) in [
+            ("outputs", output),
+            ("visualizations", PathBuf::from(&vis)),
+            ("visualizations", thumbnail),
+        ] {
+            if let Some(name) = file.file_name() {
+                files.push((format!("{}/{}", dir, name.to_string_lossy()), file));
+            }
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let json = serde_json::to_string_pretty(run)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    zip.start_file("results.json", options)?;
+    zip.write_all(json.as_bytes())?;
+    // Archived after the run finished, but only ever written for it
+    if let Some(report) = report {
+        zip.start_file("report.html", options)?;
+        zip.write_all(&fs::read(report)?)?;
+    }
+
+    let mut stale = 0;
+    for (name, file) in files {
+        let Ok(modified) = fs::metadata(&file).and_then(|m| m.modified()) else {
+            continue;
+        };
+        if finished.is_some_and(|finished| modified > finished) {
+            stale += 1;
+            continue;
+        }
+        zip.start_file(name, options)?;
+        zip.write_all(&fs::read(&file)?)?;
+    }
+    zip.finish()?;
+    Ok(stale)
+}
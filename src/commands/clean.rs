@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::results::results_path;
+
+pub fn clean(config: &Config) {
+    let dirs = [
+        Some(&config.paths.visualizer_dir),
+        Some(&config.paths.output_dir),
+        config.paths.logs_dir.as_ref(),
+    ];
+    for dir in dirs.into_iter().flatten() {
+        let path = Path::new(dir);
+        if path.exists() {
+            match fs::remove_dir_all(path) {
+                Ok(()) => eprintln!("Removed {}", dir),
+                Err(e) => eprintln!("Error removing {}: {}", dir, e),
+            }
+        }
+    }
+
+    let files = [
+        Path::new(&config.paths.html_output).to_path_buf(),
+        results_path(config),
+    ];
+    for path in &files {
+        if path.exists() {
+            match fs::remove_file(path) {
+                Ok(()) => eprintln!("Removed {}", path.display()),
+                Err(e) => eprintln!("Error removing {}: {}", path.display(), e),
+            }
+        }
+    }
+}
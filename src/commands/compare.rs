@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::cli::CompareArgs;
+use crate::html::generate_compare_html;
+use crate::results::{load_results, load_seed_scores, Result};
+use crate::runner::extract_number;
+use crate::scoring::Objective;
+use crate::stats::wilcoxon_signed_rank;
+
+/// How many of the largest swings each way the comparison lists.
+const LARGEST_SWINGS: usize = 5;
+
+/// Scores of one seed in the two compared runs.
+pub struct CaseDiff {
+    pub seed: usize,
+    pub base: i64,
+    pub target: i64,
+}
+
+impl CaseDiff {
+    pub fn delta(&self) -> i64 {
+        self.target.saturating_sub(self.base)
+    }
+
+    /// Relative change against the base score, in percent. Any change from a
+    /// base of 0, as a failed seed scores, is infinite so no tolerance
+    /// makes it a tie.
+    pub fn delta_percent(&self) -> f64 {
+        if self.base == 0 {
+            match self.delta() {
+                0 => 0.0,
+                delta => f64::INFINITY.copysign(delta as f64),
+            }
+        } else {
+            self.delta() as f64 / self.base.abs() as f64 * 100.0
+        }
+    }
+}
+
+/// Scores of another run shown alongside the comparison, for reference.
+pub struct ExtraColumn {
+    pub name: String,
+    pub scores: HashMap<usize, i64>,
+}
+
+impl ExtraColumn {
+    /// The seed's score as a table cell, `-` when the seed is missing.
+    pub fn cell(&self, seed: usize) -> String {
+        self.scores
+            .get(&seed)
+            .map_or_else(|| "-".to_string(), |score| score.to_string())
+    }
+}
+
+pub struct Comparison {
+    pub objective: Objective,
+    pub base_name: String,
+    pub target_name: String,
+    pub cases: Vec<CaseDiff>,
+    pub extras: Vec<ExtraColumn>,
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+    pub tie_tolerance: f64,
+    pub delta_sum: i64,
+    /// Seeds that gained or lost the most, largest swing first
+    pub largest_wins: Vec<usize>,
+    pub largest_losses: Vec<usize>,
+    /// Wilcoxon signed-rank p-value of the per-seed differences; `None` when
+    /// no seed differs
+    pub p_value: Option<f64>,
+}
+
+impl Comparison {
+    /// Pairs up seeds present in both runs; seeds missing from either side are ignored.
+    /// A seed whose score changed by at most `tie_tolerance` percent of its
+    /// base score counts as a tie.
+    pub fn new(
+        objective: Objective,
+        tie_tolerance: f64,
+        base_name: &str,
+        base: &[(usize, i64)],
+        target_name: &str,
+        target: &[(usize, i64)],
+    ) -> Self {
+        let base_scores: HashMap<usize, i64> = base.iter().copied().collect();
+        let mut cases: Vec<CaseDiff> = target
+            .iter()
+            .filter_map(|&(seed, score)| {
+                base_scores.get(&seed).map(|&base| CaseDiff {
+                    seed,
+                    base,
+                    target: score,
+                })
+            })
+            .collect();
+        cases.sort_by_key(|c| c.seed);
+
+        let beyond_tolerance = |c: &CaseDiff| c.delta_percent().abs() > tie_tolerance;
+        let wins = cases
+            .iter()
+            .filter(|c| objective.gain(c.delta()) > 0 && beyond_tolerance(c))
+            .count();
+        let losses = cases
+            .iter()
+            .filter(|c| objective.gain(c.delta()) < 0 && beyond_tolerance(c))
+            .count();
+        let ties = cases.len() - wins - losses;
+
+        let mut by_gain: Vec<usize> = (0..cases.len()).collect();
+        by_gain.sort_by_key(|&i| objective.gain(cases[i].delta()));
+        let largest_losses = by_gain
+            .iter()
+            .copied()
+            .take_while(|&i| objective.gain(cases[i].delta()) < 0)
+            .take(LARGEST_SWINGS)
+            .collect();
+        let largest_wins = by_gain
+            .iter()
+            .rev()
+            .copied()
+            .take_while(|&i| objective.gain(cases[i].delta()) > 0)
+            .take(LARGEST_SWINGS)
+            .collect();
+        let delta_sum = cases
+            .iter()
+            .map(|c| c.delta())
+            .fold(0i64, |sum, delta| sum.saturating_add(delta));
+        let deltas: Vec<i64> = cases.iter().map(|c| c.delta()).collect();
+        let p_value = wilcoxon_signed_rank(&deltas);
+
+        Comparison {
+            objective,
+            base_name: base_name.to_string(),
+            target_name: target_name.to_string(),
+            cases,
+            extras: Vec::new(),
+            wins,
+            losses,
+            ties,
+            tie_tolerance,
+            delta_sum,
+            largest_wins,
+            largest_losses,
+            p_value,
+        }
+    }
+
+    /// Wins, losses and ties, with the tolerance when ties include small changes.
+    pub fn win_loss_tie(&self) -> String {
+        let mut wlt = format!("{}/{}/{}", self.wins, self.losses, self.ties);
+        if self.tie_tolerance > 0.0 {
+            wlt.push_str(&format!(" (ties within {}%)", self.tie_tolerance));
+        }
+        wlt
+    }
+
+    /// A seed's change, as in `12 (+340, +1.25%)`, or `12 (+340)` from a
+    /// base of 0.
+    pub fn swing(&self, case: &CaseDiff) -> String {
+        if case.base == 0 {
+            return format!("{} ({:+})", case.seed, case.delta());
+        }
+        format!(
+            "{} ({:+}, {:+.2}%)",
+            case.seed,
+            case.delta(),
+            case.delta_percent()
+        )
+    }
+
+    /// One line on whether the difference is likely to be noise.
+    pub fn significance(&self) -> String {
+        match self.p_value {
+            Some(p) => format!(
+                "Wilcoxon signed-rank test: p = {:.4} ({})",
+                p,
+                if p < 0.05 {
+                    "significant at 5%"
+                } else {
+                    "could be noise"
+                }
+            ),
+            None => "Wilcoxon signed-rank test: no seed differs".to_string(),
+        }
+    }
+}
+
+pub fn compare(args: &CompareArgs) {
+    let base = match load_results(Path::new(&args.base)) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Error loading {}: {}", args.base, e);
+            return;
+        }
+    };
+    let target = match load_results(Path::new(&args.target)) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Error loading {}: {}", args.target, e);
+            return;
+        }
+    };
+
+    let mut extras = Vec::new();
+    for path in &args.extras {
+        match load_seed_scores(Path::new(path)) {
+            Ok(scores) => extras.push(ExtraColumn {
+                name: path.clone(),
+                scores: scores.into_iter().collect(),
+            }),
+            Err(e) => {
+                eprintln!("Error loading {}: {}", path, e);
+                return;
+            }
+        }
+    }
+
+    // Result files record the objective they were scored with
+    let mut comparison = Comparison::new(
+        args.objective.unwrap_or(target.objective),
+        args.tie_tolerance,
+        &args.base,
+        &seed_scores(&base.results),
+        &args.target,
+        &seed_scores(&target.results),
+    );
+    comparison.extras = extras;
+
+    let mut header = format!(
+        "{:>6} {:>14} {:>14} {:>14}",
+        "seed", "base", "target", "delta"
+    );
+    for extra in &comparison.extras {
+        header.push_str(&format!(" {:>14}", extra_label(&extra.name)));
+    }
+    println!("{}", header);
+    for case in &comparison.cases {
+        let mut line = format!(
+            "{:>6} {:>14} {:>14} {:>+14}",
+            case.seed,
+            case.base,
+            case.target,
+            case.delta()
+        );
+        for extra in &comparison.extras {
+            line.push_str(&format!(" {:>14}", extra.cell(case.seed)));
+        }
+        println!("{}", line);
+    }
+
+    println!("Win/Loss/Tie: {}", comparison.win_loss_tie());
+    for (label, swings) in [
+        ("Largest gains", &comparison.largest_wins),
+        ("Largest losses", &comparison.largest_losses),
+    ] {
+        if !swings.is_empty() {
+            let list: Vec<String> = swings
+                .iter()
+                .map(|&i| comparison.swing(&comparison.cases[i]))
+                .collect();
+            println!("{}: {}", label, list.join(", "));
+        }
+    }
+    println!("Summed difference: {:+}", comparison.delta_sum);
+    println!("{}", comparison.significance());
+    // Both runs combined the target's way, as its report's headline score
+    println!(
+        "{}: {} -> {} ({})",
+        target.score_label(),
+        target.headline_score_of(&base),
+        target.headline_score(),
+        target.headline_delta(&base).0
+    );
+
+    if let Some(ref html_output) = args.html {
+        generate_compare_html(&comparison, html_output);
+        println!("Comparison saved to {}", html_output);
+    }
+}
+
+/// File stem of an extra column's file, short enough for a column header.
+fn extra_label(name: &str) -> String {
+    Path::new(name)
+        .file_stem()
+        .map_or_else(|| name.to_string(), |s| s.to_string_lossy().to_string())
+}
+
+fn seed_scores(results: &[Result]) -> Vec<(usize, i64)> {
+    results
+        .iter()
+        .map(|r| (extract_number(&r.input_file), r.score))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_from_zero_is_beyond_any_tolerance() {
+        let case = |base, target| CaseDiff {
+            seed: 0,
+            base,
+            target,
+        };
+        assert_eq!(case(0, 0).delta_percent(), 0.0);
+        assert_eq!(case(0, 5).delta_percent(), f64::INFINITY);
+        assert_eq!(case(0, -5).delta_percent(), f64::NEG_INFINITY);
+        assert_eq!(case(200, 201).delta_percent(), 0.5);
+
+        // Seed 0 recovers from a failure, seed 1 fails, seed 2 is a tie within 1%
+        let base = [(0, 0), (1, 500), (2, 200)];
+        let target = [(0, 3), (1, 0), (2, 201)];
+        let comparison = Comparison::new(Objective::Max, 1.0, "a", &base, "b", &target);
+        assert_eq!(comparison.wins, 1);
+        assert_eq!(comparison.losses, 1);
+        assert_eq!(comparison.ties, 1);
+    }
+}
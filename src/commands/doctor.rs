@@ -0,0 +1,323 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{load_config, unknown_placeholders, Config, Overrides};
+use crate::runner::{command_args, get_input_files};
+use crate::visualizer::{DEFAULT_COMMAND, DEFAULT_THUMBNAIL_COMMAND};
+
+/// Tally of the checks, printed as they run.
+#[derive(Default)]
+struct Checks {
+    problems: usize,
+}
+
+impl Checks {
+    fn pass(&self, what: &str) {
+        println!("  ok  {}", what);
+    }
+
+    fn fail(&mut self, what: &str, hint: &str) {
+        println!("  !!  {}\n      {}", what, hint);
+        self.problems += 1;
+    }
+}
+
+/// Checks the config and everything it points to before a run would trip
+/// over it once per seed. Returns whether no problems were found.
+pub fn doctor(path: &str, overrides: &Overrides) -> bool {
+    let mut checks = Checks::default();
+    let config = match load_config(path, overrides) {
+        Ok(config) => {
+            checks.pass(&format!("{} loads", path));
+            config
+        }
+        Err(e) => {
+            checks.fail(
+                &format!("the config does not load: {}", e),
+                "`score_visualizer init` writes a starting config",
+            );
+            return false;
+        }
+    };
+
+    check_inputs(&mut checks, &config);
+    check_dirs(&mut checks, &config);
+    check_placeholders(&mut checks, &config);
+    check_commands(&mut checks, &config);
+
+    if checks.problems == 0 {
+        println!("No problems found");
+    } else if checks.problems == 1 {
+        println!("1 problem found");
+    } else {
+        println!("{} problems found", checks.problems);
+    }
+    checks.problems == 0
+}
+
+fn check_inputs(checks: &mut Checks, config: &Config) {
+    let input_dir = &config.paths.input_dir;
+    match get_input_files(input_dir, config.tester.seeds.as_ref()) {
+        Ok(files) if files.is_empty() => checks.fail(
+            &format!("no .txt inputs in {}", input_dir),
+            "generate them with the tools' gen, or check tester.seeds",
+        ),
+        Ok(files) => checks.pass(&format!("{} inputs in {}", files.len(), input_dir)),
+        Err(e) => checks.fail(
+            &format!("paths.input_dir {} cannot be read: {}", input_dir, e),
+            "point it at the in/ directory of the official tools",
+        ),
+    }
+}
+
+/// Output directories need not exist yet, as long as they can be created.
+fn check_dirs(checks: &mut Checks, config: &Config) {
+    let paths = &config.paths;
+    let html_dir = Path::new(&paths.html_output)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string());
+    let dirs = [
+        ("paths.output_dir", Some(&paths.output_dir)),
+        ("paths.visualizer_dir", Some(&paths.visualizer_dir)),
+        ("the directory of paths.html_output", html_dir.as_ref()),
+        ("paths.answers_dir", paths.answers_dir.as_ref()),
+        ("paths.logs_dir", paths.logs_dir.as_ref()),
+        ("paths.cache_dir", paths.cache_dir.as_ref()),
+    ];
+    for (name, dir) in dirs {
+        let Some(dir) = dir.filter(|dir| !dir.is_empty()) else {
+            continue;
+        };
+        let path = Path::new(dir);
+        if path.is_dir() {
+            checks.pass(&format!("{} {} exists", name, dir));
+        } else if path.exists() {
+            checks.fail(
+                &format!("{} {} is not a directory", name, dir),
+                "move the file away or choose another path",
+            );
+        } else if creatable(path) {
+            checks.pass(&format!("{} {} will be created", name, dir));
+        } else {
+            checks.fail(
+                &format!("{} {} cannot be created", name, dir),
+                "a parent of it is a file; check the path for typos",
+            );
+        }
+    }
+
+    let working_dirs = [
+        (
+            "visualizer.working_dir",
+            config
+                .visualizer
+                .as_ref()
+                .and_then(|v| v.working_dir.as_ref()),
+        ),
+        (
+            "scorer.working_dir",
+            config.scorer.as_ref().and_then(|s| s.working_dir.as_ref()),
+        ),
+    ];
+    for (name, dir) in working_dirs {
+        let Some(dir) = dir else {
+            continue;
+        };
+        if Path::new(dir).is_dir() {
+            checks.pass(&format!("{} {} exists", name, dir));
+        } else {
+            checks.fail(
+                &format!("{} {} does not exist", name, dir),
+                "commands run there; check the path for typos",
+            );
+        }
+    }
+
+    let scripts = [
+        ("tester.script", config.tester.script.as_ref()),
+        ("tester.solver_script", config.tester.solver_script.as_ref()),
+    ];
+    for (name, script) in scripts {
+        let Some(script) = script else {
+            continue;
+        };
+        if Path::new(script).exists() {
+            checks.pass(&format!("{} {} exists", name, script));
+        } else {
+            checks.fail(
+                &format!("{} {} does not exist", name, script),
+                "it is relative to the directory score_visualizer runs in",
+            );
+        }
+    }
+}
+
+/// The nearest existing ancestor of `path` is a directory.
+fn creatable(path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.as_os_str().is_empty() || dir.exists())
+        .is_some_and(|dir| dir.as_os_str().is_empty() || dir.is_dir())
+}
+
+/// Every `{{name}}` must be one the command is given; the tester's are
+/// checked when the config loads.
+fn check_placeholders(checks: &mut Checks, config: &Config) {
+    let vis = config.visualizer.as_ref();
+    let templates = [
+        (
+            "scorer.command",
+            config.scorer.as_ref().map(|s| s.command.as_str()),
+            &["input", "output"][..],
+        ),
+        (
+            "visualizer.command",
+            vis.and_then(|v| v.command.as_deref()),
+            &["input_file", "output_file", "vis_output", "input", "output"][..],
+        ),
+        (
+            "visualizer.thumbnail_command",
+            vis.and_then(|v| v.thumbnail_command.as_deref()),
+            &["vis_file", "thumbnail_file"][..],
+        ),
+    ];
+    for (name, template, available) in templates {
+        let Some(template) = template else {
+            continue;
+        };
+        let available: Vec<String> = available.iter().map(|name| name.to_string()).collect();
+        let unknown = unknown_placeholders(template, &available);
+        if unknown.is_empty() {
+            continue;
+        }
+        checks.fail(
+            &format!(
+                "{} uses {}, which nothing fills in",
+                name,
+                unknown.join(", ")
+            ),
+            &format!(
+                "available: {}",
+                available
+                    .iter()
+                    .map(|name| format!("{{{{{}}}}}", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
+    }
+}
+
+/// The program of every command that will run can be found and executed.
+fn check_commands(checks: &mut Checks, config: &Config) {
+    let tools_dir = Path::new(&config.paths.input_dir)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_string_lossy()
+        .to_string();
+    let mut placeholders = vec![("tools_dir", tools_dir.as_str())];
+    if let Some(ref script) = config.tester.script {
+        placeholders.push(("script", script));
+    }
+    if let Some(ref solver_script) = config.tester.solver_script {
+        placeholders.push(("solver_script", solver_script));
+    }
+
+    let mut commands: Vec<(&str, &str, Option<&str>)> = Vec::new();
+    if config.tester.use_shell {
+        checks.pass("tester.command runs through the shell; its programs are not checked");
+    } else {
+        commands.push(("tester.command", &config.tester.command, None));
+    }
+    if let Some(tester) = config.tester.interactive_tester() {
+        commands.push(("tester.interactive_tester", tester, None));
+    }
+    if let Some(ref scorer) = config.scorer {
+        commands.push((
+            "scorer.command",
+            &scorer.command,
+            scorer.working_dir.as_deref(),
+        ));
+    }
+    let vis = config.visualizer.as_ref();
+    if vis.is_none_or(|v| v.enabled) {
+        commands.push((
+            "visualizer.command",
+            vis.and_then(|v| v.command.as_deref())
+                .unwrap_or(DEFAULT_COMMAND),
+            vis.and_then(|v| v.working_dir.as_deref()),
+        ));
+        if vis.is_some_and(|v| v.thumbnails) {
+            commands.push((
+                "visualizer.thumbnail_command",
+                vis.and_then(|v| v.thumbnail_command.as_deref())
+                    .unwrap_or(DEFAULT_THUMBNAIL_COMMAND),
+                None,
+            ));
+        }
+    }
+
+    for (name, template, working_dir) in commands {
+        let Some(program) =
+            command_args(template, &placeholders).and_then(|args| args.into_iter().next())
+        else {
+            checks.fail(&format!("{} is empty", name), "give the command to run");
+            continue;
+        };
+        match find_program(&program, working_dir) {
+            Some(path) => checks.pass(&format!("{} runs {}", name, path.display())),
+            None if program.contains('/') || program.contains('\\') => checks.fail(
+                &format!("{} runs {}, which is not an executable file", name, program),
+                &match working_dir {
+                    Some(dir) => {
+                        format!("the path is relative to {}; build it or fix the path", dir)
+                    }
+                    None => "build it, chmod +x it, or fix the path".to_string(),
+                },
+            ),
+            None => checks.fail(
+                &format!("{} runs {}, which is not on the PATH", name, program),
+                "install it or give its full path",
+            ),
+        }
+    }
+}
+
+/// Resolves `program` like the OS would: a path relative to `working_dir`,
+/// or a name searched for on the PATH.
+fn find_program(program: &str, working_dir: Option<&str>) -> Option<PathBuf> {
+    if program.contains('/') || program.contains('\\') {
+        let path = match working_dir {
+            Some(dir) => Path::new(dir).join(program),
+            None => PathBuf::from(program),
+        };
+        return executable(&path).then_some(path);
+    }
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd", ".bat"]
+    } else {
+        &[""]
+    };
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|path| executable(path))
+    })
+}
+
+fn executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
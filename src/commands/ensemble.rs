@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::EnsembleArgs;
+use crate::config::Config;
+use crate::history::{history_path, History};
+use crate::html::generate_ensemble_html;
+use crate::results::{load_results, RunResults, Verdict};
+use crate::runner::extract_number;
+use crate::scoring::Objective;
+
+/// The best answer found for one seed and the run it came from.
+pub struct SeedBest {
+    pub seed: usize,
+    pub score: i64,
+    /// Index into `Ensemble::sources`
+    pub source: usize,
+}
+
+/// One of the runs the ensemble picks from.
+pub struct Source {
+    pub name: String,
+    pub total_score: i64,
+    /// Seeds where this run had the best answer, ties going to the earlier run
+    pub seeds_won: usize,
+}
+
+/// The hypothetical run made of the best answer per seed across several runs.
+pub struct Ensemble {
+    pub objective: Objective,
+    pub sources: Vec<Source>,
+    pub seeds: Vec<SeedBest>,
+    pub total_score: i64,
+}
+
+impl Ensemble {
+    /// Picks the best answer of every seed. Accepted answers beat failed
+    /// ones whatever their score, so a failure scored 0 never wins a
+    /// minimization.
+    pub fn new(objective: Objective, runs: &[(String, RunResults)]) -> Self {
+        let mut best: BTreeMap<usize, (bool, i64, usize)> = BTreeMap::new();
+        for (index, (_, run)) in runs.iter().enumerate() {
+            for result in &run.results {
+                let seed = extract_number(&result.input_file);
+                let accepted = result.verdict == Verdict::Ac;
+                let better = best.get(&seed).is_none_or(|&(best_accepted, score, _)| {
+                    (accepted, objective.gain(result.score))
+                        > (best_accepted, objective.gain(score))
+                });
+                if better {
+                    best.insert(seed, (accepted, result.score, index));
+                }
+            }
+        }
+
+        let mut sources: Vec<Source> = runs
+            .iter()
+            .map(|(name, run)| Source {
+                name: name.clone(),
+                total_score: run.total_score,
+                seeds_won: 0,
+            })
+            .collect();
+        let seeds: Vec<SeedBest> = best
+            .into_iter()
+            .map(|(seed, (_, score, source))| {
+                sources[source].seeds_won += 1;
+                SeedBest {
+                    seed,
+                    score,
+                    source,
+                }
+            })
+            .collect();
+        let total_score = seeds
+            .iter()
+            .fold(0i64, |sum, s| sum.saturating_add(s.score));
+
+        Ensemble {
+            objective,
+            sources,
+            seeds,
+            total_score,
+        }
+    }
+
+    /// The source with the best total on its own.
+    pub fn best_single(&self) -> Option<&Source> {
+        // max_by_key keeps the last of equals; prefer the earlier run
+        self.sources
+            .iter()
+            .rev()
+            .max_by_key(|s| self.objective.gain(s.total_score))
+    }
+}
+
+pub fn ensemble(config: Option<&Config>, args: &EnsembleArgs) {
+    let runs = match config {
+        Some(config) => match history_runs(config, args.last) {
+            Ok(runs) => runs,
+            Err(e) => {
+                eprintln!("Error reading history: {}", e);
+                return;
+            }
+        },
+        None => {
+            let mut runs = Vec::new();
+            for path in &args.files {
+                match load_results(Path::new(path)) {
+                    Ok(run) => runs.push((path.clone(), run)),
+                    Err(e) => {
+                        eprintln!("Error loading {}: {}", path, e);
+                        return;
+                    }
+                }
+            }
+            runs
+        }
+    };
+    if runs.len() < 2 {
+        eprintln!("An ensemble needs at least two runs, found {}", runs.len());
+        return;
+    }
+
+    let objective = args.objective.unwrap_or_else(|| match config {
+        Some(config) => config
+            .scoring
+            .as_ref()
+            .map(|s| s.objective)
+            .unwrap_or_default(),
+        // Result files record the objective they were scored with
+        None => runs[0].1.objective,
+    });
+    let ensemble = Ensemble::new(objective, &runs);
+
+    println!("{:>6} {:>14}  source", "seed", "best");
+    for seed in &ensemble.seeds {
+        println!(
+            "{:>6} {:>14}  {}",
+            seed.seed, seed.score, ensemble.sources[seed.source].name
+        );
+    }
+    println!();
+    println!("{:>14} {:>10}  source", "total", "seeds won");
+    for source in &ensemble.sources {
+        println!(
+            "{:>14} {:>10}  {}",
+            source.total_score, source.seeds_won, source.name
+        );
+    }
+    println!("Ensemble total: {}", ensemble.total_score);
+    if let Some(best) = ensemble.best_single() {
+        println!(
+            "Best single run: {} ({}); the ensemble gains {:+}",
+            best.total_score,
+            best.name,
+            ensemble.total_score.saturating_sub(best.total_score)
+        );
+    }
+
+    if let Some(ref html_output) = args.html {
+        generate_ensemble_html(&ensemble, html_output);
+        println!("Ensemble saved to {}", html_output);
+    }
+}
+
+/// The recorded runs, oldest first, limited to the `last` most recent ones.
+fn history_runs(
+    config: &Config,
+    last: Option<usize>,
+) -> rusqlite::Result<Vec<(String, RunResults)>> {
+    let history = History::open(Path::new(history_path(config)))?;
+    let mut summaries = history.run_summaries()?;
+    if let Some(last) = last {
+        let skip = summaries.len().saturating_sub(last);
+        summaries.drain(..skip);
+    }
+    summaries
+        .iter()
+        .map(|summary| {
+            let run = history.load_run(summary.id)?;
+            Ok((format!("run #{} ({})", summary.id, summary.timestamp), run))
+        })
+        .collect()
+}
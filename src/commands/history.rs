@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use crate::cli::HistoryArgs;
+use crate::config::Config;
+use crate::history::{history_path, History, RunSummary};
+use crate::scoring::load_weights;
+
+/// Prints the recorded runs as a table, oldest first so the latest ends up
+/// next to the prompt. Scores are combined as `scoring` says now, as the
+/// report's headline score is.
+pub fn history(config: &Config, args: &HistoryArgs) {
+    let aggregate = config
+        .scoring
+        .as_ref()
+        .map(|s| s.aggregate)
+        .unwrap_or_default();
+    let weights = load_weights(config);
+    let (summaries, headline_scores) = match History::open(Path::new(history_path(config)))
+        .and_then(|history| {
+            Ok((
+                history.run_summaries()?,
+                history.headline_scores(aggregate, &weights)?,
+            ))
+        }) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            return;
+        }
+    };
+    let mut runs: Vec<_> = summaries
+        .into_iter()
+        .filter(|run| args.tags.iter().all(|tag| run.tags.contains(tag)))
+        .collect();
+    if runs.is_empty() {
+        println!("No recorded runs in {}", history_path(config));
+        return;
+    }
+
+    let precision = aggregate.precision();
+    let score = |run: &RunSummary| match headline_scores.get(&run.id) {
+        Some(score) => format!("{:.*}", precision, score),
+        None => run.total_score.to_string(),
+    };
+    // Deltas against the previous listed full run, so a tag filter compares
+    // the runs of one line of work; a run over only some seeds compares with
+    // none
+    let mut previous: Option<&RunSummary> = None;
+    let mut deltas: Vec<String> = Vec::with_capacity(runs.len());
+    for run in &runs {
+        if run.partial {
+            deltas.push("partial".to_string());
+            continue;
+        }
+        deltas.push(match previous {
+            Some(before) => match (
+                headline_scores.get(&run.id),
+                headline_scores.get(&before.id),
+            ) {
+                (Some(score), Some(before)) => format!("{:+.*}", precision, score - before),
+                _ => format!("{:+}", run.total_score.saturating_sub(before.total_score)),
+            },
+            None => String::new(),
+        });
+        previous = Some(run);
+    }
+    if let Some(limit) = args.limit {
+        let skip = runs.len().saturating_sub(limit);
+        runs.drain(..skip);
+        deltas.drain(..skip);
+    }
+
+    println!(
+        "{:>5}  {:<19} {:>6} {:>14} {:>12}  {:<16} comment",
+        "run",
+        "timestamp",
+        "cases",
+        aggregate.score_label(&weights).to_lowercase(),
+        "delta",
+        "tags"
+    );
+    for (run, delta) in runs.iter().zip(deltas) {
+        let line = format!(
+            "{:>5}  {:<19} {:>6} {:>14} {:>12}  {:<16} {}",
+            format!("#{}", run.id),
+            run.timestamp,
+            run.num_cases,
+            score(run),
+            delta,
+            run.tags.join(","),
+            run.comment.as_deref().unwrap_or("")
+        );
+        println!("{}", line.trim_end());
+    }
+}
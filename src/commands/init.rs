@@ -0,0 +1,184 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::cli::InitArgs;
+use crate::config::parse_config;
+use crate::scoring::Objective;
+
+/// Where the official tools are usually unpacked, relative to the
+/// directory `score_visualizer` runs in.
+const TOOLS_CANDIDATES: [&str; 2] = ["tools", "../tools"];
+
+/// Writes a config for the AHC tools found nearby, asking for what cannot
+/// be detected unless `--yes` is given or stdin is not a terminal.
+pub fn init(path: &str, args: &InitArgs) {
+    if Path::new(path).exists() && !args.force {
+        eprintln!(
+            "Error: {} already exists; pass --force to overwrite it",
+            path
+        );
+        return;
+    }
+    let interactive = !args.yes && io::stdin().is_terminal();
+
+    let detected = TOOLS_CANDIDATES
+        .iter()
+        .find(|dir| Path::new(dir).join("in").is_dir());
+    let tools = match args.tools {
+        Some(ref tools) => tools.clone(),
+        None => ask(
+            interactive,
+            "Directory of the AHC tools",
+            detected.copied().unwrap_or("tools"),
+        ),
+    };
+    // [paths] are read relative to the config file; a config written
+    // elsewhere gets absolute paths so they still find the tools
+    let config_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    let tools = if config_dir.as_os_str().is_empty() || config_dir == Path::new(".") {
+        tools
+    } else {
+        std::path::absolute(&tools)
+            .map(|tools| tools.to_string_lossy().to_string())
+            .unwrap_or(tools)
+    };
+    let tools_dir = Path::new(&tools);
+    match fs::read_dir(tools_dir.join("in")) {
+        Ok(entries) => eprintln!(
+            "Found {} input files in {}/in",
+            entries.count(),
+            tools.trim_end_matches('/')
+        ),
+        Err(_) => eprintln!(
+            "Warning: {}/in does not exist yet; generate the inputs there before running",
+            tools.trim_end_matches('/')
+        ),
+    }
+
+    let solver = match args.command {
+        Some(ref command) => command.clone(),
+        None => ask(
+            interactive,
+            "Command running your solver (reads the input on stdin)",
+            "./a.out",
+        ),
+    };
+    // Interactive problems ship a tester that runs the solver itself
+    let interactive_problem = tools_dir.join("src/bin/tester.rs").is_file()
+        || tools_dir.join("target/release/tester").is_file();
+    if interactive_problem {
+        eprintln!("Interactive problem: the solver runs through the tools' tester");
+    }
+
+    let objective = match args.objective {
+        Some(objective) => objective,
+        None if ask(interactive, "Is a higher score better? (y/n)", "y")
+            .to_lowercase()
+            .starts_with('n') =>
+        {
+            Objective::Min
+        }
+        None => Objective::Max,
+    };
+
+    let vis_built = tools_dir.join("target/release/vis").is_file();
+    let visualizer = if vis_built || tools_dir.join("Cargo.toml").is_file() {
+        // No working_dir: each visualization then runs in a scratch directory of its
+        // own, in parallel, instead of taking turns writing vis.html in the tools
+        let command = if vis_built {
+            let vis = tools_dir.join("target/release/vis");
+            format!(
+                "{} {{{{input_file}}}} {{{{output_file}}}}",
+                shell_words::quote(&vis.to_string_lossy())
+            )
+        } else {
+            let manifest = tools_dir.join("Cargo.toml");
+            format!(
+                "cargo run -r --manifest-path {} --bin vis {{{{input_file}}}} {{{{output_file}}}}",
+                shell_words::quote(&manifest.to_string_lossy())
+            )
+        };
+        format!(
+            "[visualizer]\ncommand = {}\noutput = \"vis.html\"\n",
+            quote(&command)
+        )
+    } else {
+        eprintln!(
+            "No visualizer found in {}; visualization is disabled",
+            tools
+        );
+        "[visualizer]\nenabled = false\n".to_string()
+    };
+    if (interactive_problem || vis_built) && !tools_dir.join("target/release").is_dir() {
+        eprintln!("Build the tools first: cargo build -r in {}", tools);
+    }
+
+    let results = format!("{}/results", tools.trim_end_matches('/'));
+    let config = format!(
+        "# Written by `score_visualizer init`; see the config.toml in the repository
+# for every option
+
+[paths]
+input_dir = {input_dir}
+output_dir = {output_dir}
+visualizer_dir = {visualizer_dir}
+html_output = {html_output}
+
+[tester]
+# {{{{input_file}}}}, {{{{output_file}}}}, {{{{seed}}}} and {{{{tools_dir}}}} are replaced per case
+command = {command}
+{interactive}time_limit_ms = 10000
+
+{visualizer}
+[scoring]
+objective = \"{objective}\"
+",
+        input_dir = quote(&format!("{}/in", tools.trim_end_matches('/'))),
+        output_dir = quote(&format!("{}/out", tools.trim_end_matches('/'))),
+        visualizer_dir = quote(&format!("{}/visualizations", results)),
+        html_output = quote(&format!("{}/index.html", results)),
+        command = quote(&solver),
+        interactive = if interactive_problem {
+            "interactive = true\n"
+        } else {
+            ""
+        },
+        visualizer = visualizer,
+        objective = match objective {
+            Objective::Max => "max",
+            Objective::Min => "min",
+        },
+    );
+
+    // Catch a command that does not split, say, before the first run does
+    if let Err(e) = parse_config(&config) {
+        eprintln!("Error: the generated config is invalid: {}", e);
+        return;
+    }
+    match fs::write(path, config) {
+        Ok(()) => println!("Config written to {}; try `score_visualizer run`", path),
+        Err(e) => eprintln!("Error writing {}: {}", path, e),
+    }
+}
+
+/// Asks on stderr with `default` in brackets; an empty answer, or no
+/// terminal to ask on, takes the default.
+fn ask(interactive: bool, question: &str, default: &str) -> String {
+    if !interactive {
+        return default.to_string();
+    }
+    eprint!("{} [{}]: ", question, default);
+    io::stderr().flush().ok();
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok();
+    match answer.trim() {
+        "" => default.to_string(),
+        answer => answer.to_string(),
+    }
+}
+
+/// A TOML string literal.
+fn quote(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
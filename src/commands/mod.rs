@@ -0,0 +1,15 @@
+pub mod archive;
+pub mod clean;
+pub mod compare;
+pub mod doctor;
+pub mod ensemble;
+pub mod history;
+pub mod init;
+pub mod optimize;
+pub mod prune;
+pub mod report;
+pub mod run;
+pub mod serve;
+pub mod sweep;
+pub mod vis;
+pub mod watch;
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+
+use crate::cli::OptimizeArgs;
+use crate::commands::sweep::{
+    format_params, print_trials, run_trial, trial_inputs, write_trials_csv, Trial,
+};
+use crate::config::Config;
+use crate::interrupt::RunGuard;
+use crate::runner::extract_number;
+use crate::seeds::make_rng;
+use crate::sweep::{can_vary, neighbour, random_params};
+
+/// Starting step of the hill climb, as a fraction of each range.
+const INITIAL_STEP: f64 = 0.2;
+/// The step grows by this factor after an improvement and shrinks by its
+/// fourth root after a failure, settling near one success in five.
+const STEP_GROWTH: f64 = 1.5;
+const MIN_STEP: f64 = 0.01;
+const MAX_STEP: f64 = 0.5;
+/// How many of the best trials are printed at the end.
+const BEST_TRIALS: usize = 10;
+
+/// Everything `optimize` has tried, with the seeds it was evaluated on;
+/// totals over other seeds would not compare.
+#[derive(Default, Serialize, Deserialize)]
+struct TrialHistory {
+    seeds: Vec<usize>,
+    trials: Vec<Trial>,
+}
+
+/// Hill climbing over `sweep.ranges` and `sweep.params`: each trial runs a
+/// neighbour of the best parameters so far on the same seeds and becomes
+/// the new best when it scores better.
+pub fn optimize(config: &Config, args: &OptimizeArgs) {
+    let Some(ref sweep_config) = config.sweep else {
+        eprintln!("Error: no [sweep] section in the config");
+        return;
+    };
+    if !can_vary(sweep_config) {
+        eprintln!("Error: the [sweep] section has no parameter to vary");
+        return;
+    }
+    let (input_files, tools_dir) = match trial_inputs(config, &args.sweep) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("Error reading input files: {}", e);
+            return;
+        }
+    };
+    let seeds: Vec<usize> = input_files.iter().map(|f| extract_number(f)).collect();
+    let history_path = sweep_config
+        .optimize_history
+        .as_deref()
+        .unwrap_or(".ahc_optimize.json");
+    let mut history = match load_history(history_path) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Error loading {}: {}", history_path, e);
+            return;
+        }
+    };
+    let names: BTreeSet<&String> = sweep_config
+        .params
+        .keys()
+        .chain(sweep_config.ranges.keys())
+        .collect();
+    if history.trials.is_empty() {
+        history.seeds = seeds;
+    } else if history.seeds != seeds {
+        eprintln!(
+            "Error: {} was evaluated on other seeds; use the same --seeds/--quick/--rng-seed \
+             or remove it to start over",
+            history_path
+        );
+        return;
+    } else if history
+        .trials
+        .iter()
+        .any(|t| t.params.keys().collect::<BTreeSet<_>>() != names)
+    {
+        eprintln!(
+            "Error: {} tried other parameters than [sweep] defines; remove it to start over",
+            history_path
+        );
+        return;
+    }
+
+    let objective = config
+        .scoring
+        .as_ref()
+        .map(|s| s.objective)
+        .unwrap_or_default();
+    let mut best = history
+        .trials
+        .iter()
+        .max_by_key(|t| objective.gain(t.total_score))
+        .cloned();
+    if let Some(ref best) = best {
+        eprintln!(
+            "Resuming from {} trials; best {} -> {}",
+            history.trials.len(),
+            format_params(&best.params),
+            best.total_score
+        );
+    }
+
+    let budget = args.trials.unwrap_or(sweep_config.trials);
+    eprintln!(
+        "Optimizing over {} seeds for {} trials",
+        input_files.len(),
+        budget
+    );
+    let mut rng = make_rng(args.sweep.rng_seed);
+    let mut step = INITIAL_STEP;
+    let _running = RunGuard::new();
+    for i in 0..budget {
+        let params = match best {
+            Some(ref best) => match neighbour(sweep_config, &best.params, step, &mut rng) {
+                Some(params) => params,
+                None => {
+                    eprintln!("Nothing to vary around {}", format_params(&best.params));
+                    break;
+                }
+            },
+            None => random_params(sweep_config, &mut rng),
+        };
+        let label = format!("{}/{}", i + 1, budget);
+        // Small steps revisit parameters; their total is already known
+        if let Some(seen) = history.trials.iter().find(|t| t.params == params) {
+            eprintln!(
+                "[{}] {} -> {} (tried before)",
+                label,
+                format_params(&params),
+                seen.total_score
+            );
+            step = (step / STEP_GROWTH.powf(0.25)).max(MIN_STEP);
+            continue;
+        }
+        let Some(trial) = run_trial(config, &params, &input_files, &tools_dir, &label) else {
+            eprintln!("Stopped after {} of {} trials", i, budget);
+            break;
+        };
+        let improved = best
+            .as_ref()
+            .is_none_or(|b| objective.gain(trial.total_score.saturating_sub(b.total_score)) > 0);
+        eprintln!(
+            "[{}] {} -> {}{}",
+            label,
+            format_params(&params),
+            trial.total_score,
+            if improved { " (best)" } else { "" }
+        );
+        step = if improved {
+            (step * STEP_GROWTH).min(MAX_STEP)
+        } else {
+            (step / STEP_GROWTH.powf(0.25)).max(MIN_STEP)
+        };
+        history.trials.push(trial.clone());
+        if improved {
+            best = Some(trial);
+        }
+        // Saved after every trial so an aborted session loses nothing
+        if let Err(e) = save_history(history_path, &history) {
+            eprintln!("Error saving {}: {}", history_path, e);
+        }
+    }
+
+    let mut ranked = history.trials.clone();
+    ranked.sort_by_key(|t| std::cmp::Reverse(objective.gain(t.total_score)));
+    ranked.truncate(BEST_TRIALS);
+    print_trials(&ranked, objective);
+    println!("Trial history saved to {}", history_path);
+    if let Some(ref output) = sweep_config.output {
+        match write_trials_csv(&history.trials, output) {
+            Ok(()) => println!("Trials saved to {}", output),
+            Err(e) => eprintln!("Error writing trials: {}", e),
+        }
+    }
+}
+
+fn load_history(path: &str) -> io::Result<TrialHistory> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(TrialHistory::default()),
+        Err(e) => Err(e),
+    }
+}
+
+fn save_history(path: &str, history: &TrialHistory) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(history)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, json)
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::cli::PruneArgs;
+use crate::config::Config;
+use crate::history::{finished_at, history_path, History, RunSummary};
+use crate::html::{archived_report_path, generate_trend_html};
+use crate::runner::{get_input_files, output_path};
+
+/// Deletes the artifacts of old runs: their archived reports and log
+/// directories, and the outputs, visualizations and logs no later run has
+/// rewritten. The history database, best answers and the latest report are
+/// kept.
+pub fn prune(config: &Config, args: &PruneArgs) {
+    let runs = match History::open(Path::new(history_path(config)))
+        .and_then(|history| history.run_summaries())
+    {
+        Ok(runs) => runs,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            return;
+        }
+    };
+
+    let now = SystemTime::now();
+    let age_cutoff = args
+        .older_than
+        .map(|days| now - Duration::from_secs(days * 24 * 60 * 60));
+    let kept_from = args
+        .keep_last
+        .map_or(0, |keep| runs.len().saturating_sub(keep));
+    let pruned_runs: Vec<&RunSummary> = runs
+        .iter()
+        .enumerate()
+        .filter(|&(i, run)| {
+            i < kept_from
+                || age_cutoff
+                    .is_some_and(|cutoff| finished_at(&run.timestamp).is_some_and(|t| t < cutoff))
+        })
+        .map(|(_, run)| run)
+        .collect();
+    // Per-seed files are rewritten by every run, so a file untouched since
+    // the newest pruned run finished belongs to pruned runs only
+    let count_cutoff = kept_from
+        .checked_sub(1)
+        .and_then(|i| finished_at(&runs[i].timestamp))
+        .map(|t| t + Duration::from_secs(1));
+    let cutoff = match (age_cutoff, count_cutoff) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+
+    let mut files: Vec<PathBuf> = pruned_runs
+        .iter()
+        .map(|run| archived_report_path(config, run.id))
+        .filter(|path| path.is_file())
+        .collect();
+    let pruned_logs: Vec<&String> = pruned_runs
+        .iter()
+        .filter_map(|run| run.logs_dir.as_ref())
+        .collect();
+    for dir in &pruned_logs {
+        if let Ok(entries) = fs::read_dir(dir) {
+            files.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+    if let Some(cutoff) = cutoff {
+        files.extend(stale_files(config, cutoff));
+    }
+
+    let mut removed = 0;
+    let mut bytes = 0;
+    for path in &files {
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if args.dry_run {
+            println!("{}", path.display());
+        } else if let Err(e) = fs::remove_file(path) {
+            eprintln!("Error removing {}: {}", path.display(), e);
+            continue;
+        }
+        removed += 1;
+        bytes += size;
+    }
+    if !args.dry_run {
+        for dir in &pruned_logs {
+            // Only once emptied
+            let _ = fs::remove_dir(dir);
+        }
+    }
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    println!(
+        "{} {} files ({:.1} MB) of {} old runs",
+        verb,
+        removed,
+        bytes as f64 / 1e6,
+        pruned_runs.len()
+    );
+    if !args.dry_run && !pruned_runs.is_empty() {
+        // Drop the links to the removed reports
+        if let Err(e) = History::open(Path::new(history_path(config)))
+            .and_then(|history| generate_trend_html(&history, config))
+        {
+            eprintln!("Error updating the trend page: {}", e);
+        }
+    }
+}
+
+/// Outputs, visualizations and logs last written before `cutoff`. Only the
+/// solver outputs are taken from `output_dir`, which often also holds the
+/// reports and answers.
+fn stale_files(config: &Config, cutoff: SystemTime) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = get_input_files(&config.paths.input_dir, None)
+        .unwrap_or_default()
+        .iter()
+        .map(|input| PathBuf::from(output_path(&config.paths.output_dir, input)))
+        .collect();
+    for dir in [
+        Some(&config.paths.visualizer_dir),
+        config.paths.logs_dir.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if let Ok(entries) = fs::read_dir(dir) {
+            candidates.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+
+    let html_output = Path::new(&config.paths.html_output);
+    candidates.sort();
+    candidates.dedup();
+    candidates
+        .into_iter()
+        .filter(|path| path.is_file() && path != html_output)
+        .filter(|path| {
+            config
+                .paths
+                .answers_dir
+                .as_ref()
+                .is_none_or(|answers| !path.starts_with(answers))
+        })
+        .filter(|path| {
+            fs::metadata(path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+        .collect()
+}
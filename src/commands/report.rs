@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use crate::browser::open_report;
+use crate::cli::ReportArgs;
+use crate::config::Config;
+use crate::html::generate_html;
+use crate::junit::write_junit;
+use crate::markdown::generate_markdown;
+use crate::pahcer::write_pahcer;
+use crate::results::{load_results, results_path, write_csv};
+use crate::scoring::{apply_relative_scores, load_weights};
+
+pub fn report(config: &Config, args: &ReportArgs) {
+    let path = results_path(config);
+    let mut run = match load_results(&path) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Error loading results from {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let baseline = args
+        .baseline
+        .as_ref()
+        .and_then(|path| match load_results(Path::new(path)) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("Error loading baseline {}: {}", path, e);
+                None
+            }
+        });
+
+    if let Some(ref scoring) = config.scoring {
+        run.objective = scoring.objective;
+        run.aggregate = scoring.aggregate;
+    }
+    run.weights = load_weights(config);
+    run.total_relative_score = apply_relative_scores(&mut run.results, config);
+
+    generate_html(&run, baseline.as_ref(), config);
+    generate_markdown(&run, baseline.as_ref(), config);
+
+    if let Some(csv_output) = &config.paths.csv_output {
+        if let Err(e) = write_csv(&run.results, csv_output) {
+            eprintln!("Error writing CSV file: {}", e);
+        }
+    }
+    if let Some(pahcer_output) = &config.paths.pahcer_output {
+        if let Err(e) = write_pahcer(&run, pahcer_output) {
+            eprintln!("Error writing pahcer results: {}", e);
+        }
+    }
+    if let Some(junit_output) = &config.paths.junit_output {
+        if let Err(e) = write_junit(&run, junit_output) {
+            eprintln!("Error writing JUnit XML: {}", e);
+        }
+    }
+
+    println!("{} Score: {}", run.score_label(), run.headline_score());
+    println!("Results saved to {}", config.paths.html_output);
+
+    if args.open || config.open_report() {
+        open_report(&config.paths.html_output);
+    }
+}
@@ -0,0 +1,980 @@
+use indicatif::{
+    HumanDuration, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write as FmtWrite};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::browser::open_report;
+use crate::cache::ResultCache;
+use crate::cli::RunArgs;
+use crate::config::Config;
+use crate::features::{parse_features, FeaturesConfig};
+use crate::gate::{self, EarlyStop};
+use crate::git::git_info;
+use crate::history::{history_path, History};
+use crate::hooks::run_hooks;
+use crate::html::{
+    archived_report_path, generate_html, generate_progress_html, generate_trend_html,
+};
+use crate::interrupt::{interrupted, stop_run, RunGuard};
+use crate::junit::write_junit;
+use crate::markdown::{generate_markdown, write_step_summary};
+use crate::notify::notify;
+use crate::pahcer::write_pahcer;
+use crate::progress::{log, BarsGuard, RunLogGuard};
+use crate::results::{
+    load_results, results_path, save_results, total_score, write_csv, Result, RunResults, Verdict,
+};
+use crate::runner::{
+    create_logs_dir, extract_number, format_score, get_input_files, output_path, process_file,
+};
+use crate::scoring::{
+    apply_relative_scores, best_scores_store, load_best_scores_store, load_weights,
+    save_best_scores, update_best_scores, Objective,
+};
+use crate::seeds::{sample_inputs, stratified_sample};
+use crate::tui::Tui;
+use crate::visualizer::{vis_threads, visualize_result, visualize_selected};
+
+/// Returns false when the run could not be started or met a `run.fail_on`
+/// condition.
+pub fn run(config: &Config, args: &RunArgs) -> bool {
+    run_with_observer(config, args, |_| {})
+}
+
+/// Runs like `run`, calling `on_result` for every case as soon as it has
+/// been scored and visualized.
+pub fn run_with_observer(
+    config: &Config,
+    args: &RunArgs,
+    mut on_result: impl FnMut(&Result),
+) -> bool {
+    let config = &config.with_params(args.params.iter().map(|(name, value)| (name, value)));
+    let input_dir = &config.paths.input_dir;
+    let output_dir = &config.paths.output_dir;
+    let visualizer_dir = &config.paths.visualizer_dir;
+    let html_output = &config.paths.html_output;
+    let tools_dir = Path::new(input_dir)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let hooks = config.hooks.clone().unwrap_or_default();
+    if let Err(e) = run_hooks("pre_run", &hooks.pre_run, &[]) {
+        log!("Error: {}; skipping the run", e);
+        return false;
+    }
+    // After pre_run, which may build or commit the solver
+    let git = git_info(config);
+
+    // Create output directories
+    fs::create_dir_all(output_dir).ok();
+    fs::create_dir_all(visualizer_dir).ok();
+    let (run_logs, _run_log) = match config.paths.logs_dir {
+        Some(ref logs_dir) => match start_run_logs(logs_dir) {
+            Ok((dir, guard)) => (Some(dir), Some(guard)),
+            Err(e) => {
+                log!("Error creating the run's log directory: {}", e);
+                fs::create_dir_all(logs_dir).ok();
+                (None, None)
+            }
+        },
+        None => (None, None),
+    };
+
+    // Get input files
+    let seeds = args.seeds.as_ref().or(config.tester.seeds.as_ref());
+    let mut input_files = match get_input_files(input_dir, seeds) {
+        Ok(files) => files,
+        Err(e) => {
+            log!("Error reading input files: {}", e);
+            return false;
+        }
+    };
+
+    // Sort input files by number
+    input_files.sort_by_key(|f| extract_number(f));
+
+    // Narrow the run down to seeds that failed or regressed last time
+    let previous = if args.only_failed || args.only_regressed {
+        let path = results_path(config);
+        match load_results(&path) {
+            Ok(previous) => Some(previous),
+            Err(e) => {
+                log!(
+                    "Error loading previous results from {}: {}",
+                    path.display(),
+                    e
+                );
+                return false;
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(ref previous) = previous {
+        let rerun = rerun_seeds(config, args, previous);
+        let available = input_files.len();
+        input_files.retain(|f| rerun.contains(&extract_number(f)));
+        log!("Rerunning {} of {} seeds", input_files.len(), available);
+    }
+
+    let sampled_from = args.quick.map(|count| {
+        let available = input_files.len();
+        input_files = match (args.stratify, stratify_feature(config)) {
+            (Some(strata), Some((features, feature))) => {
+                let values: Vec<Option<f64>> = input_files
+                    .iter()
+                    .map(|file| {
+                        let input = fs::read_to_string(file).ok()?;
+                        parse_features(&input, features).get(feature).copied()
+                    })
+                    .collect();
+                stratified_sample(&input_files, &values, count, strata, args.rng_seed)
+            }
+            (Some(_), None) => {
+                log!("[WARN] --stratify needs [features] in the config; sampling uniformly");
+                sample_inputs(&input_files, count, args.rng_seed)
+            }
+            (None, _) => sample_inputs(&input_files, count, args.rng_seed),
+        };
+        available
+    });
+
+    // Longest seeds first, by their last recorded time, so no slow seed is
+    // left running alone at the end; seeds never timed count as longest
+    let seed_times = History::open(Path::new(history_path(config)))
+        .and_then(|h| h.seed_times())
+        .unwrap_or_else(|e| {
+            log!("Error reading run history: {}", e);
+            HashMap::new()
+        });
+    input_files.sort_by_key(|file| {
+        std::cmp::Reverse(
+            seed_times
+                .get(&extract_number(file))
+                .copied()
+                .unwrap_or(u64::MAX),
+        )
+    });
+    let num_threads = config
+        .parallel
+        .as_ref()
+        .and_then(|p| p.num_threads)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+    let eta = WeightedEta::new(&input_files, &seed_times, num_threads);
+
+    // Score and visualize files in parallel
+    let total_inputs = input_files.len() as u64;
+    let objective = config
+        .scoring
+        .as_ref()
+        .map(|s| s.objective)
+        .unwrap_or_default();
+    let mut tui = if args.tui {
+        match Tui::start(input_files.len(), objective) {
+            Ok(tui) => Some(tui),
+            Err(e) => {
+                log!("Error starting TUI: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    // The TUI replaces the progress bars
+    let bars = MultiProgress::with_draw_target(if tui.is_some() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    });
+    let _bars_guard = BarsGuard::new(&bars);
+    let score_bar = bars.add(ProgressBar::new(total_inputs));
+    // The running summary goes on a line of its own below the bar
+    let style = ProgressStyle::with_template(
+        "{spinner:.cyan} {prefix:<12} {bar:40.cyan/blue} {pos:>3}/{len:<3} {percent:>3}% | {per_sec} | ETA {eta}\n  {msg}",
+    )
+    .unwrap()
+    .progress_chars("=>-");
+    // Recorded seed times give a better ETA than the mean time per seed so far
+    let style = match eta {
+        Some(ref eta) => {
+            let eta = eta.clone();
+            style.with_key("eta", move |_: &ProgressState, w: &mut dyn FmtWrite| {
+                let _ = write!(w, "{:#}", HumanDuration(eta.remaining()));
+            })
+        }
+        None => style,
+    };
+    score_bar.set_style(style);
+    score_bar.set_prefix("Scoring");
+    score_bar.set_message("No seed scored yet");
+    let vis_enabled = !args.no_vis && config.visualizer.as_ref().is_none_or(|v| v.enabled);
+    // The worst seeds are only known once every seed is scored
+    let worst_k = config
+        .visualizer
+        .as_ref()
+        .and_then(|v| v.worst_k)
+        .filter(|_| vis_enabled);
+    let vis_streaming = vis_enabled && worst_k.is_none();
+    let vis_bar = if vis_streaming {
+        let bar = bars.add(ProgressBar::new(total_inputs));
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} {msg:<12} {bar:40.green/blue} {pos:>3}/{len:<3} {percent:>3}% | {per_sec} | ETA {eta}",
+            )
+            .unwrap()
+            .progress_chars("=>-"),
+        );
+        bar.set_message("Visualizing");
+        Some(bar)
+    } else {
+        None
+    };
+    // One line per scoring thread with the seed it is running
+    let worker_style = ProgressStyle::with_template("  {spinner:.cyan} {prefix} {msg}").unwrap();
+    let workers: Vec<ProgressBar> = (0..num_threads.min(input_files.len()))
+        .map(|i| {
+            let worker = bars.add(ProgressBar::new_spinner());
+            worker.set_style(worker_style.clone());
+            worker.set_prefix(format!("#{:<2}", i + 1));
+            worker.enable_steady_tick(Duration::from_millis(100));
+            worker
+        })
+        .collect();
+    let _running = RunGuard::new();
+    let cache = match config.paths.cache_dir {
+        Some(ref dir) if !args.no_cache => match ResultCache::new(dir, config) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                log!("Error opening result cache {}: {}", dir, e);
+                None
+            }
+        },
+        _ => None,
+    };
+    let cache_hits = Arc::new(AtomicUsize::new(0));
+    let cache_hits_for_thread = Arc::clone(&cache_hits);
+
+    // Scored results go through the visualizer pool on their way to `rx`
+    let (scored_tx, scored_rx) = mpsc::channel::<Result>();
+    let (tx, rx) = mpsc::channel::<Result>();
+    let input_files_for_thread = input_files.clone();
+    let output_dir_for_thread = output_dir.to_string();
+    let score_bar_for_thread = score_bar.clone();
+    let workers_for_thread = workers.clone();
+    let tools_dir_for_thread = tools_dir.clone();
+    let mut config_for_thread = config.clone();
+    if let Some(ref dir) = run_logs {
+        config_for_thread.paths.logs_dir = Some(dir.clone());
+    }
+
+    let eta_for_thread = eta.clone();
+    let producer = thread::spawn(move || {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            input_files_for_thread
+                .iter()
+                // Unlike par_iter's splitting, the bridge hands out seeds in order
+                .par_bridge()
+                .for_each_with(scored_tx, |sender, input_file| {
+                    // After Ctrl-C only the seeds already running are finished
+                    if interrupted() {
+                        return;
+                    }
+                    let worker =
+                        rayon::current_thread_index().and_then(|i| workers_for_thread.get(i));
+                    if let Some(worker) = worker {
+                        worker.set_message(format!("seed {}", extract_number(input_file)));
+                    }
+                    let result = match cache.as_ref().and_then(|cache| {
+                        cache.get(
+                            input_file,
+                            &output_path(&output_dir_for_thread, input_file),
+                            &config_for_thread,
+                        )
+                    }) {
+                        Some(cached) => {
+                            cache_hits_for_thread.fetch_add(1, Ordering::Relaxed);
+                            if let Some(ref eta) = eta_for_thread {
+                                eta.skip(cached.seed);
+                            }
+                            cached
+                        }
+                        None => {
+                            let result = process_file(
+                                input_file,
+                                &output_dir_for_thread,
+                                &config_for_thread,
+                                &tools_dir_for_thread,
+                            );
+                            if let Some(ref cache) = cache {
+                                if let Err(e) = cache.put(&result) {
+                                    log!("Error caching result for {}: {}", input_file, e);
+                                }
+                            }
+                            if let Some(ref eta) = eta_for_thread {
+                                eta.finish(&result);
+                            }
+                            result
+                        }
+                    };
+                    if let Some(worker) = worker {
+                        worker.set_message("");
+                    }
+                    score_bar_for_thread.inc(1);
+                    let _ = sender.send(result);
+                });
+        });
+    });
+
+    let output_dir_for_vis = output_dir.to_string();
+    let visualizer_dir_for_vis = visualizer_dir.to_string();
+    let tools_dir_for_vis = tools_dir.clone();
+    let config_for_vis = config.clone();
+    let visualizer = thread::spawn(move || {
+        if !vis_streaming {
+            for result in scored_rx {
+                let _ = tx.send(result);
+            }
+            return;
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(vis_threads(&config_for_vis))
+            .build()
+            .unwrap();
+        pool.scope(|scope| {
+            for result in scored_rx {
+                let tx = tx.clone();
+                let (output_dir, visualizer_dir, tools_dir, config) = (
+                    &output_dir_for_vis,
+                    &visualizer_dir_for_vis,
+                    &tools_dir_for_vis,
+                    &config_for_vis,
+                );
+                scope.spawn(move |_| {
+                    let result =
+                        visualize_result(result, output_dir, visualizer_dir, tools_dir, config);
+                    let _ = tx.send(result);
+                });
+            }
+        });
+    });
+
+    // Pick the baseline before this run is recorded in the history
+    let baseline = match args.baseline {
+        Some(ref path) => match load_results(Path::new(path)) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                log!("Error loading baseline {}: {}", path, e);
+                None
+            }
+        },
+        None => History::open(Path::new(history_path(config)))
+            .and_then(|history| history.last_full_run())
+            .unwrap_or_else(|e| {
+                log!("Error reading run history: {}", e);
+                None
+            }),
+    };
+
+    let max_failures = config.run.as_ref().and_then(|r| r.max_failures);
+    let max_failures = if args.fail_fast {
+        Some(max_failures.unwrap_or(1))
+    } else {
+        max_failures
+    };
+    let mut failures = 0;
+    let mut early_stop = match (args.early_stop, baseline.as_ref()) {
+        (Some(threshold), Some(baseline)) => Some(EarlyStop::new(baseline, objective, threshold)),
+        (Some(_), None) => {
+            log!("[WARN] --early-stop needs a baseline; no earlier run to compare with");
+            None
+        }
+        (None, _) => None,
+    };
+    let mut stopped_early = None;
+    let mut summary = RunningSummary::new(objective);
+
+    let mut results: Vec<Result> = Vec::with_capacity(total_inputs as usize);
+    loop {
+        let result = match tui {
+            Some(ref mut tui) => match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(result) => result,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let _ = tui.poll(Duration::ZERO);
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            },
+            None => match rx.recv() {
+                Ok(result) => result,
+                Err(_) => break,
+            },
+        };
+        if let Some(ref bar) = vis_bar {
+            bar.inc(1);
+        }
+        summary.push(&result);
+        score_bar.set_message(summary.to_string());
+        on_result(&result);
+        if let Some(ref mut tui) = tui {
+            let _ = tui.push(&result);
+        }
+        if result.verdict != Verdict::Ac {
+            failures += 1;
+            if max_failures == Some(failures) {
+                log!(
+                    "Stopping after {} failed seed(s); waiting for running seeds to finish",
+                    failures
+                );
+                stop_run();
+            }
+        }
+        if let Some(reason) = early_stop.as_mut().and_then(|e| e.push(&result)) {
+            if stopped_early.is_none() && !interrupted() {
+                log!(
+                    "Stopping early: {}; waiting for running seeds to finish",
+                    reason
+                );
+                stop_run();
+                stopped_early = Some(reason);
+            }
+        }
+        results.push(result);
+        // Refresh the report so a long run can be inspected before it ends
+        if let Some(every) = args.update_every {
+            if results.len().is_multiple_of(every) && results.len() < input_files.len() {
+                let mut partial = build_run_results(config, results.clone());
+                partial.sampled_from = sampled_from;
+                partial.subset = args.seeds.is_some();
+                partial.git = git.clone();
+                partial.logs_dir = run_logs.clone();
+                generate_progress_html(&partial, baseline.as_ref(), input_files.len(), config);
+            }
+        }
+    }
+    let _ = producer.join();
+    let _ = visualizer.join();
+    if let Some(tui) = tui {
+        if let Err(e) = tui.finish() {
+            log!("Error in TUI: {}", e);
+        }
+    }
+    for worker in &workers {
+        worker.finish_and_clear();
+    }
+    score_bar.set_prefix("Scoring done");
+    score_bar.finish();
+    let cache_hits = cache_hits.load(Ordering::Relaxed);
+    if cache_hits > 0 {
+        log!("Reused {} cached results", cache_hits);
+    }
+    if let Some(ref bar) = vis_bar {
+        bar.finish_with_message("Visualizing done");
+    }
+
+    if let Some(k) = worst_k {
+        let worst = objective.worst_seeds(&results, k);
+        log!("Visualizing the {} worst seeds", worst.len());
+        results = visualize_selected(results, config, |result| worst.contains(&result.seed));
+    }
+
+    // Before the seeds that were not rerun are filled in, which makes any
+    // rerun look complete
+    let ran = results.len();
+    let stopped = stopped_short(interrupted(), ran, input_files.len());
+    let results = merge_rerun(results, previous);
+
+    let mut run_results = build_run_results(config, results);
+    run_results.sampled_from = sampled_from;
+    run_results.subset = args.seeds.is_some();
+    run_results.git = git;
+    run_results.logs_dir = run_logs.clone();
+    run_results.comment = args.comment.clone();
+    run_results.tags = args.tags.clone();
+    run_results.interrupted = stopped;
+    let total_score = run_results.total_score;
+
+    // Record the run in the history database before the reports so they
+    // show its id; a partial run would make a misleading baseline
+    if run_results.interrupted {
+        log!(
+            "Stopped after {} of {} seeds; not recorded in the history",
+            ran,
+            input_files.len()
+        );
+    } else {
+        match record_history(config, &run_results) {
+            Ok(run_id) => run_results.run_id = Some(run_id),
+            Err(e) => log!("Error recording run history: {}", e),
+        }
+    }
+
+    // Generate HTML
+    generate_html(&run_results, baseline.as_ref(), config);
+    generate_markdown(&run_results, baseline.as_ref(), config);
+    if let Err(e) = write_step_summary(&run_results, baseline.as_ref(), args.gha_summary.as_deref())
+    {
+        log!("Error writing step summary: {}", e);
+    }
+
+    if let Some(csv_output) = &config.paths.csv_output {
+        if let Err(e) = write_csv(&run_results.results, csv_output) {
+            log!("Error writing CSV file: {}", e);
+        }
+    }
+    if let Some(pahcer_output) = &config.paths.pahcer_output {
+        if let Err(e) = write_pahcer(&run_results, pahcer_output) {
+            log!("Error writing pahcer results: {}", e);
+        }
+    }
+    if let Some(junit_output) = &config.paths.junit_output {
+        if let Err(e) = write_junit(&run_results, junit_output) {
+            log!("Error writing JUnit XML: {}", e);
+        }
+    }
+
+    // Write machine-readable results; also used by `report` and `compare`
+    let results_json = results_path(config);
+    if let Err(e) = save_results(&results_json, &run_results) {
+        log!("Error writing results JSON: {}", e);
+    }
+
+    // Archive the report of a recorded run and refresh the trend page
+    if let Some(run_id) = run_results.run_id {
+        if let Err(e) = archive_run(config, run_id) {
+            log!("Error updating the trend page: {}", e);
+        }
+    }
+
+    // Keep the best score, and answer, found so far for each seed
+    if let Some(store) = best_scores_store(config) {
+        match save_best(&run_results, &store, config.paths.answers_dir.as_deref()) {
+            Ok(improved) => log!(
+                "Best scores updated in {} ({} seeds improved)",
+                store,
+                improved
+            ),
+            Err(e) => log!("Error updating best scores: {}", e),
+        }
+    }
+
+    println!(
+        "{} Score: {}",
+        run_results.score_label(),
+        run_results.headline_score()
+    );
+    if let Some(total_relative) = run_results.total_relative_score {
+        println!("Total Relative Score: {}", total_relative);
+    }
+    if let (Some(available), Some(extrapolated)) = (sampled_from, run_results.extrapolated_total())
+    {
+        let interval = run_results
+            .extrapolated_interval()
+            .map_or_else(String::new, |(low, high)| {
+                format!(" (95% CI {} to {})", low, high)
+            });
+        println!(
+            "Quick run: {} of {} seeds, extrapolated total {}{}",
+            run_results.results.len(),
+            available,
+            extrapolated,
+            interval
+        );
+    }
+    if let Some(ref baseline) = baseline {
+        println!(
+            "Delta vs baseline: {}",
+            run_results.headline_delta(baseline).0
+        );
+    }
+    println!("Results saved to {}", html_output);
+    println!("JSON results saved to {}", results_json.display());
+    if let Some(ref dir) = run_logs {
+        println!("Logs saved to {}", dir);
+    }
+
+    let mut gate_failures = match config.run {
+        Some(ref run_config) => gate::check(run_config, &run_results, baseline.as_ref()),
+        None => Vec::new(),
+    };
+    if let Some(reason) = stopped_early {
+        gate_failures.push(format!("stopped early: {}", reason));
+    }
+    for failure in &gate_failures {
+        log!("[FAIL] {}", failure);
+    }
+
+    if args.open || config.open_report() {
+        open_report(html_output);
+    }
+
+    let envs = [
+        ("SCORE_VISUALIZER_TOTAL", total_score.to_string()),
+        ("SCORE_VISUALIZER_REPORT", html_output.to_string()),
+        (
+            "SCORE_VISUALIZER_RESULTS",
+            results_json.display().to_string(),
+        ),
+    ];
+    if let Err(e) = run_hooks("post_run", &hooks.post_run, &envs) {
+        log!("Error: {}", e);
+    }
+    if let Some(ref notify_config) = config.notify {
+        if let Err(e) = notify(notify_config, &run_results, baseline.as_ref(), html_output) {
+            log!("Error sending notification: {}", e);
+        }
+    }
+    gate_failures.is_empty()
+}
+
+/// Seeds selected by `--only-failed` and `--only-regressed`. Regressions
+/// are judged against `--baseline`, or else the run recorded before `previous`.
+fn rerun_seeds(config: &Config, args: &RunArgs, previous: &RunResults) -> HashSet<usize> {
+    let mut seeds = HashSet::new();
+    if args.only_failed {
+        seeds.extend(
+            previous
+                .results
+                .iter()
+                .filter(|r| r.verdict != Verdict::Ac)
+                .map(|r| r.seed),
+        );
+    }
+    if args.only_regressed {
+        let reference = match args.baseline {
+            Some(ref path) => load_results(Path::new(path)).map_err(|e| e.to_string()),
+            None => History::open(Path::new(history_path(config)))
+                .and_then(|history| history.run_before_last())
+                .map_err(|e| e.to_string())
+                .and_then(|run| run.ok_or_else(|| "no earlier run in the history".to_string())),
+        };
+        match reference {
+            Ok(reference) => {
+                let scores: HashMap<usize, i64> = reference
+                    .results
+                    .iter()
+                    .map(|r| (r.seed, r.score))
+                    .collect();
+                seeds.extend(
+                    previous
+                        .results
+                        .iter()
+                        .filter(|r| {
+                            scores.get(&r.seed).is_some_and(|&before| {
+                                previous.objective.gain(r.score.saturating_sub(before)) < 0
+                            })
+                        })
+                        .map(|r| r.seed),
+                );
+            }
+            Err(e) => log!("Error loading the run to find regressions against: {}", e),
+        }
+    }
+    seeds
+}
+
+/// The feature `--stratify` splits seeds by: `features.group_by`, or else
+/// the first named feature.
+fn stratify_feature(config: &Config) -> Option<(&FeaturesConfig, &str)> {
+    let features = config.features.as_ref()?;
+    let feature = match features.group_by {
+        Some(ref feature) => feature.as_str(),
+        None => features.columns().next()?,
+    };
+    Some((features, feature))
+}
+
+/// Sorts finished cases by seed and totals them into a run stamped with
+/// the current time.
+fn build_run_results(config: &Config, mut results: Vec<Result>) -> RunResults {
+    // Sort results by file number
+    results.sort_by_key(|r| extract_number(&r.input_file));
+
+    // Calculate total score
+    let total_score = total_score(&results);
+
+    // Get current timestamp in JST
+    let jst_now = chrono::Local::now();
+    let timestamp = jst_now.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let mut run_results = RunResults::new(timestamp, total_score, results);
+    run_results.params = config.param_values();
+    if let Some(ref scoring) = config.scoring {
+        run_results.objective = scoring.objective;
+        run_results.aggregate = scoring.aggregate;
+    }
+    run_results.weights = load_weights(config);
+    run_results.total_relative_score = apply_relative_scores(&mut run_results.results, config);
+    run_results
+}
+
+/// Records the improved seeds in the best score store and copies their
+/// outputs to `answers_dir`, so a worse run never replaces a saved answer.
+/// Returns how many seeds improved.
+fn save_best(
+    run_results: &RunResults,
+    store: &str,
+    answers_dir: Option<&str>,
+) -> io::Result<usize> {
+    let mut best_scores = load_best_scores_store(store)?;
+    let improved = update_best_scores(
+        &mut best_scores,
+        &run_results.results,
+        run_results.objective,
+    );
+    if let Some(answers_dir) = answers_dir {
+        fs::create_dir_all(answers_dir)?;
+        for result in run_results
+            .results
+            .iter()
+            .filter(|r| improved.contains(&r.seed))
+        {
+            let Some(file_name) = Path::new(&result.output_file).file_name() else {
+                continue;
+            };
+            let dest = Path::new(answers_dir).join(file_name);
+            if let Err(e) = fs::copy(&result.output_file, &dest) {
+                log!("Error copying {}: {}", result.output_file, e);
+            }
+        }
+        log!("Answers saved to {}", answers_dir);
+    }
+    if let Some(parent) = Path::new(store).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    save_best_scores(store, &best_scores)?;
+    Ok(improved.len())
+}
+
+fn record_history(config: &Config, run_results: &RunResults) -> rusqlite::Result<i64> {
+    let config_snapshot = toml::to_string(config).unwrap_or_default();
+    let mut history = History::open(Path::new(history_path(config)))?;
+    let run_id = history.record_run(run_results, &config_snapshot)?;
+    log!("Recorded run #{} in {}", run_id, history_path(config));
+    Ok(run_id)
+}
+
+/// Whether a stopped run is missing some of the `planned` seeds, counting
+/// only the `ran` seeds this run scored itself.
+fn stopped_short(interrupted: bool, ran: usize, planned: usize) -> bool {
+    interrupted && ran < planned
+}
+
+/// Fills in the seeds a rerun left out from the previous results.
+fn merge_rerun(mut results: Vec<Result>, previous: Option<RunResults>) -> Vec<Result> {
+    if let Some(previous) = previous {
+        let rerun: HashSet<usize> = results.iter().map(|r| r.seed).collect();
+        results.extend(
+            previous
+                .results
+                .into_iter()
+                .filter(|r| !rerun.contains(&r.seed)),
+        );
+    }
+    results
+}
+
+/// Creates a new log directory for the run about to start and starts
+/// copying `log!` lines to its `run.log`. The run id is only known once the
+/// run is recorded, and runs can overlap, so the directory is not named
+/// after it.
+fn start_run_logs(logs_dir: &str) -> io::Result<(String, RunLogGuard)> {
+    let dir = create_logs_dir(logs_dir)?;
+    let run_log = fs::File::create(Path::new(&dir).join("run.log"))?;
+    Ok((dir, RunLogGuard::new(run_log)))
+}
+
+fn archive_run(config: &Config, run_id: i64) -> rusqlite::Result<()> {
+    let history = History::open(Path::new(history_path(config)))?;
+    // Keep a copy of this run's report for the trend page to link to
+    let archived = archived_report_path(config, run_id);
+    if let Err(e) = fs::copy(&config.paths.html_output, &archived) {
+        log!("Error archiving report to {}: {}", archived.display(), e);
+    }
+    generate_trend_html(&history, config)
+}
+
+/// Remaining time of the scoring, from each seed's last recorded time
+/// instead of the mean time per seed so far, which is far off when seed
+/// sizes vary.
+#[derive(Clone)]
+struct WeightedEta {
+    /// Expected milliseconds per seed; seeds never timed get the mean
+    expected: Arc<HashMap<usize, u64>>,
+    total_ms: u64,
+    /// Expected milliseconds of the finished seeds
+    done_ms: Arc<AtomicU64>,
+    /// Milliseconds the finished seeds actually took
+    taken_ms: Arc<AtomicU64>,
+    /// Expected milliseconds of the seeds taken from the cache, which take
+    /// no time and say nothing of the speed
+    skipped_ms: Arc<AtomicU64>,
+    threads: u64,
+}
+
+impl WeightedEta {
+    /// `None` when no seed of the run has a recorded time.
+    fn new(input_files: &[String], times: &HashMap<usize, u64>, threads: usize) -> Option<Self> {
+        let seeds: Vec<usize> = input_files.iter().map(|f| extract_number(f)).collect();
+        let known: Vec<u64> = seeds
+            .iter()
+            .filter_map(|seed| times.get(seed).copied())
+            .collect();
+        if known.is_empty() {
+            return None;
+        }
+        // A seed that took 0 ms still takes some time
+        let mean = (known.iter().sum::<u64>() / known.len() as u64).max(1);
+        let expected: HashMap<usize, u64> = seeds
+            .iter()
+            .map(|&seed| (seed, times.get(&seed).copied().unwrap_or(mean).max(1)))
+            .collect();
+        Some(WeightedEta {
+            total_ms: expected.values().sum(),
+            expected: Arc::new(expected),
+            done_ms: Arc::new(AtomicU64::new(0)),
+            taken_ms: Arc::new(AtomicU64::new(0)),
+            skipped_ms: Arc::new(AtomicU64::new(0)),
+            threads: threads.max(1) as u64,
+        })
+    }
+
+    fn finish(&self, result: &Result) {
+        let ms = self.expected.get(&result.seed).copied().unwrap_or(0);
+        self.done_ms.fetch_add(ms, Ordering::Relaxed);
+        self.taken_ms
+            .fetch_add(result.elapsed_ms, Ordering::Relaxed);
+    }
+
+    fn skip(&self, seed: usize) {
+        let ms = self.expected.get(&seed).copied().unwrap_or(0);
+        self.skipped_ms.fetch_add(ms, Ordering::Relaxed);
+    }
+
+    /// The expected work left spread over the threads, scaled by how long
+    /// the finished seeds took against their recorded times, which absorbs a
+    /// machine or solver faster or slower than in the history.
+    fn remaining(&self) -> Duration {
+        let done = self.done_ms.load(Ordering::Relaxed);
+        let skipped = self.skipped_ms.load(Ordering::Relaxed);
+        let left = self.total_ms.saturating_sub(done + skipped) / self.threads;
+        if done == 0 {
+            return Duration::from_millis(left);
+        }
+        let speed = self.taken_ms.load(Ordering::Relaxed) as f64 / done as f64;
+        Duration::from_millis(left).mul_f64(speed)
+    }
+}
+
+/// Total, mean and best/worst seed of the results so far, for the progress
+/// display, so an obviously bad run can be stopped early.
+struct RunningSummary {
+    objective: Objective,
+    count: usize,
+    total: i64,
+    failures: usize,
+    /// Seed and score
+    best: Option<(usize, i64)>,
+    worst: Option<(usize, i64)>,
+}
+
+impl RunningSummary {
+    fn new(objective: Objective) -> Self {
+        RunningSummary {
+            objective,
+            count: 0,
+            total: 0,
+            failures: 0,
+            best: None,
+            worst: None,
+        }
+    }
+
+    fn push(&mut self, result: &Result) {
+        let gain = |&(_, score): &(usize, i64)| self.objective.gain(score);
+        let seed_score = (result.seed, result.score);
+        self.count += 1;
+        self.total = self.total.saturating_add(result.score);
+        if result.verdict != Verdict::Ac {
+            self.failures += 1;
+        }
+        if self.best.is_none_or(|best| gain(&seed_score) > gain(&best)) {
+            self.best = Some(seed_score);
+        }
+        if self
+            .worst
+            .is_none_or(|worst| gain(&seed_score) < gain(&worst))
+        {
+            self.worst = Some(seed_score);
+        }
+    }
+}
+
+impl fmt::Display for RunningSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seed_score = |s: Option<(usize, i64)>| {
+            s.map_or("-".to_string(), |(seed, score)| {
+                format!("{} (seed {})", format_score(score), seed)
+            })
+        };
+        write!(
+            f,
+            "Total: {} | Mean: {:.1} | Best: {} | Worst: {}",
+            format_score(self.total),
+            self.total as f64 / self.count.max(1) as f64,
+            seed_score(self.best),
+            seed_score(self.worst)
+        )?;
+        if self.failures > 0 {
+            write!(f, " | Failures: {}", self.failures)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(seed: usize) -> Result {
+        Result::empty(
+            &format!("in/{:04}.txt", seed),
+            &format!("out/{:04}.txt", seed),
+        )
+    }
+
+    #[test]
+    fn interrupted_rerun_is_partial() {
+        let previous = RunResults::new(String::new(), 0, (0..4).map(case).collect());
+        // Seeds 1 and 3 were to be rerun; Ctrl-C came after seed 1
+        let ran = vec![case(1)];
+        let stopped = stopped_short(true, ran.len(), 2);
+        let merged = merge_rerun(ran, Some(previous));
+        assert!(stopped);
+        assert_eq!(merged.len(), 4);
+    }
+
+    #[test]
+    fn finished_rerun_is_complete() {
+        assert!(!stopped_short(true, 2, 2));
+        assert!(!stopped_short(false, 1, 2));
+    }
+}
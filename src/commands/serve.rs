@@ -0,0 +1,230 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::cli::ServeArgs;
+use crate::commands::run::run_with_observer;
+use crate::config::Config;
+
+/// Senders of the currently connected `/events` streams.
+type Clients = Arc<Mutex<Vec<Sender<String>>>>;
+
+pub fn serve(config: &Config, args: &ServeArgs) {
+    // Serve the directory of the report; visualizations live below it
+    let root = Path::new(&config.paths.html_output)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let report_name = Path::new(&config.paths.html_output)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "index.html".to_string());
+
+    let addr = format!("127.0.0.1:{}", args.port);
+    let server = match Server::http(&addr) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error starting server on {}: {}", addr, e);
+            return;
+        }
+    };
+    eprintln!("Serving {} at http://{}/", root.display(), addr);
+    eprintln!("Live dashboard at http://{}/live", addr);
+
+    let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+    let server_clients = Arc::clone(&clients);
+    let server_thread = thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let root = root.clone();
+            let report_name = report_name.clone();
+            let clients = Arc::clone(&server_clients);
+            // Event streams stay open, so every request gets its own thread
+            thread::spawn(move || handle(request, &root, &report_name, &clients));
+        }
+    });
+
+    if args.run {
+        run_with_observer(config, &args.run_args, |result| {
+            if let Ok(json) = serde_json::to_string(result) {
+                broadcast(&clients, &format!("event: result\ndata: {}\n\n", json));
+            }
+        });
+        broadcast(&clients, "event: done\ndata: {}\n\n");
+        eprintln!("Run finished; still serving (Ctrl-C to stop)");
+    }
+    let _ = server_thread.join();
+}
+
+fn broadcast(clients: &Clients, message: &str) {
+    let mut clients = clients.lock().unwrap();
+    clients.retain(|client| client.send(message.to_string()).is_ok());
+}
+
+fn handle(request: Request, root: &Path, report_name: &str, clients: &Clients) {
+    let url = request.url().split('?').next().unwrap_or("/").to_string();
+    let result = match url.as_str() {
+        "/live" => request.respond(html_response(LIVE_PAGE.to_string())),
+        "/events" => {
+            let (tx, rx) = mpsc::channel();
+            clients.lock().unwrap().push(tx);
+            stream_events(request, rx)
+        }
+        _ => {
+            let relative = if url == "/" {
+                report_name.to_string()
+            } else {
+                percent_decode(url.trim_start_matches('/'))
+            };
+            match resolve(root, &relative).and_then(|path| fs::read(&path).ok().map(|b| (path, b)))
+            {
+                Some((path, body)) => request.respond(
+                    Response::from_data(body)
+                        .with_header(header("Content-Type", content_type(&path))),
+                ),
+                None => request.respond(Response::from_string("Not Found").with_status_code(404)),
+            }
+        }
+    };
+    if let Err(e) = result {
+        // Clients closing the page mid-stream is routine
+        if e.kind() != io::ErrorKind::BrokenPipe && e.kind() != io::ErrorKind::ConnectionReset {
+            eprintln!("Error serving {}: {}", url, e);
+        }
+    }
+}
+
+/// Maps a request path below `root`, refusing anything that escapes it.
+fn resolve(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let path = root.join(relative);
+    path.is_file().then_some(path)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("json") => "application/json",
+        Some("csv") => "text/csv",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("js") => "text/javascript",
+        Some("css") => "text/css",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+fn html_response(body: String) -> Response<io::Cursor<Vec<u8>>> {
+    Response::from_string(body).with_header(header("Content-Type", "text/html; charset=utf-8"))
+}
+
+/// Writes server-sent events as they are broadcast, flushing each one,
+/// until the client disconnects or the sender side is dropped.
+fn stream_events(request: Request, rx: Receiver<String>) -> io::Result<()> {
+    // tiny_http buffers chunked bodies, so the response is written by hand
+    let mut writer = request.into_writer();
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+    writer.flush()?;
+    for message in rx {
+        writer.write_all(message.as_bytes())?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+const LIVE_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Live Results</title>
+    <style>
+        table {
+            width: 100%;
+            border-collapse: collapse;
+        }
+        th, td {
+            border: 1px solid #ddd;
+            padding: 8px;
+        }
+        th {
+            background-color: #f2f2f2;
+            text-align: left;
+        }
+    </style>
+</head>
+<body>
+    <h1>Live Results</h1>
+    <p id="status">Waiting for results...</p>
+    <p>Completed: <span id="count">0</span> / Total Score: <span id="total">0</span></p>
+    <table>
+        <thead>
+            <tr>
+                <th>Seed</th>
+                <th>Verdict</th>
+                <th>Score</th>
+                <th>Time (ms)</th>
+                <th>Visualizer</th>
+            </tr>
+        </thead>
+        <tbody id="rows"></tbody>
+    </table>
+    <script>
+        let count = 0;
+        let total = 0;
+        const source = new EventSource('/events');
+        source.addEventListener('result', (e) => {
+            const r = JSON.parse(e.data);
+            count += 1;
+            total += r.score;
+            document.getElementById('count').innerText = count;
+            document.getElementById('total').innerText = total;
+            document.getElementById('status').innerText = 'Running...';
+            const row = document.createElement('tr');
+            const link = r.visualizer ? '<a href="/' + r.visualizer + '" target="_blank">View</a>' : '';
+            row.innerHTML = '<td>' + r.seed + '</td><td>' + r.verdict + '</td><td>' + r.score + '</td><td>' + r.elapsed_ms + '</td><td>' + link + '</td>';
+            document.getElementById('rows').prepend(row);
+        });
+        source.addEventListener('done', () => {
+            document.getElementById('status').innerHTML = 'Done. <a href="/">Open the full report</a>';
+            source.close();
+        });
+    </script>
+</body>
+</html>
+"#;
@@ -0,0 +1,253 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cli::SweepArgs;
+use crate::config::Config;
+use crate::interrupt::{interrupted, RunGuard};
+use crate::results::{total_score, Verdict};
+use crate::runner::{create_logs_dir, extract_number, get_input_files, process_file};
+use crate::scoring::Objective;
+use crate::seeds::{make_rng, sample_inputs};
+use crate::sweep::{grid, random_params, Strategy};
+
+/// Totals of the seed set run with one set of parameters.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Trial {
+    pub params: BTreeMap<String, String>,
+    pub total_score: i64,
+    /// Seeds that were not AC
+    pub failures: usize,
+    pub mean_ms: f64,
+}
+
+/// The seeds every candidate is evaluated on: `--seeds` or `tester.seeds`,
+/// narrowed to a random `--quick` sample that stays the same for all of them.
+pub fn trial_inputs(config: &Config, args: &SweepArgs) -> io::Result<(Vec<String>, PathBuf)> {
+    let seeds = args.seeds.as_ref().or(config.tester.seeds.as_ref());
+    let mut input_files = get_input_files(&config.paths.input_dir, seeds)?;
+    input_files.sort_by_key(|f| extract_number(f));
+    if let Some(count) = args.quick {
+        input_files = sample_inputs(&input_files, count, args.rng_seed);
+    }
+    let tools_dir = Path::new(&config.paths.input_dir)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    Ok((input_files, tools_dir))
+}
+
+/// Runs every input with `params` on top of the configured `[params]`. Returns `None` when interrupted, since a partial total
+/// would not compare with the others.
+pub fn run_trial(
+    config: &Config,
+    params: &BTreeMap<String, String>,
+    input_files: &[String],
+    tools_dir: &Path,
+    label: &str,
+) -> Option<Trial> {
+    let mut config = config.with_params(params);
+    // Each trial logs to a directory of its own, as a run does
+    if let Some(ref logs_dir) = config.paths.logs_dir {
+        match create_logs_dir(logs_dir) {
+            Ok(dir) => config.paths.logs_dir = Some(dir),
+            Err(e) => eprintln!("Error creating the trial's log directory: {}", e),
+        }
+    }
+    let output_dir = &config.paths.output_dir;
+    fs::create_dir_all(output_dir).ok();
+
+    let bar = ProgressBar::new(input_files.len() as u64);
+    bar.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.cyan} {msg:<12} {bar:40.cyan/blue} {pos:>3}/{len:<3} {percent:>3}% | ETA {eta}",
+        )
+        .unwrap()
+        .progress_chars("=>-"),
+    );
+    bar.set_message(label.to_string());
+    let num_threads = config
+        .parallel
+        .as_ref()
+        .and_then(|p| p.num_threads)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .unwrap();
+    let results: Vec<_> = pool.install(|| {
+        input_files
+            .par_iter()
+            .filter_map(|input_file| {
+                if interrupted() {
+                    return None;
+                }
+                let result = process_file(input_file, output_dir, &config, tools_dir);
+                bar.inc(1);
+                Some(result)
+            })
+            .collect()
+    });
+    bar.finish_and_clear();
+    if interrupted() {
+        return None;
+    }
+
+    let total_ms: u64 = results.iter().map(|r| r.elapsed_ms).sum();
+    Some(Trial {
+        params: params.clone(),
+        total_score: total_score(&results),
+        failures: results.iter().filter(|r| r.verdict != Verdict::Ac).count(),
+        mean_ms: total_ms as f64 / results.len().max(1) as f64,
+    })
+}
+
+pub fn sweep(config: &Config, args: &SweepArgs) {
+    let Some(ref sweep_config) = config.sweep else {
+        eprintln!("Error: no [sweep] section in the config");
+        return;
+    };
+    let combinations = match sweep_config.strategy {
+        Strategy::Grid if sweep_config.params.is_empty() => Vec::new(),
+        Strategy::Grid => grid(&sweep_config.params),
+        Strategy::Random if sweep_config.params.is_empty() && sweep_config.ranges.is_empty() => {
+            Vec::new()
+        }
+        Strategy::Random => {
+            let mut rng = make_rng(args.rng_seed);
+            (0..sweep_config.trials)
+                .map(|_| random_params(sweep_config, &mut rng))
+                .collect()
+        }
+    };
+    if combinations.is_empty() {
+        eprintln!("Error: the [sweep] section defines no parameter sets to try");
+        return;
+    }
+    let (input_files, tools_dir) = match trial_inputs(config, args) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("Error reading input files: {}", e);
+            return;
+        }
+    };
+    eprintln!(
+        "Trying {} parameter sets over {} seeds",
+        combinations.len(),
+        input_files.len()
+    );
+
+    let _running = RunGuard::new();
+    let mut trials = Vec::new();
+    for (i, params) in combinations.iter().enumerate() {
+        let label = format!("{}/{}", i + 1, combinations.len());
+        match run_trial(config, params, &input_files, &tools_dir, &label) {
+            Some(trial) => {
+                eprintln!(
+                    "[{}] {} -> {}",
+                    label,
+                    format_params(params),
+                    trial.total_score
+                );
+                trials.push(trial);
+            }
+            None => {
+                eprintln!(
+                    "Stopped after {} of {} parameter sets",
+                    i,
+                    combinations.len()
+                );
+                break;
+            }
+        }
+    }
+
+    let objective = config
+        .scoring
+        .as_ref()
+        .map(|s| s.objective)
+        .unwrap_or_default();
+    if let Strategy::Random = sweep_config.strategy {
+        // Random parameter sets have no order of their own; best first
+        trials.sort_by_key(|t| std::cmp::Reverse(objective.gain(t.total_score)));
+    }
+    print_trials(&trials, objective);
+    if let Some(ref output) = sweep_config.output {
+        match write_trials_csv(&trials, output) {
+            Ok(()) => println!("Sweep results saved to {}", output),
+            Err(e) => eprintln!("Error writing sweep results: {}", e),
+        }
+    }
+}
+
+/// `NAME=value` pairs, for progress lines.
+pub fn format_params(params: &BTreeMap<String, String>) -> String {
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A table with one row per trial in the order given, marking the best total.
+pub fn print_trials(trials: &[Trial], objective: Objective) {
+    let Some(first) = trials.first() else {
+        return;
+    };
+    let best = trials
+        .iter()
+        .map(|t| objective.gain(t.total_score))
+        .max()
+        .unwrap_or_default();
+    let mut header = String::from("  ");
+    for name in first.params.keys() {
+        header.push_str(&format!("{:>12} ", name));
+    }
+    header.push_str(&format!(
+        "{:>14} {:>8} {:>10}",
+        "total", "failed", "mean ms"
+    ));
+    println!("{}", header);
+    for trial in trials {
+        let mark = if objective.gain(trial.total_score) == best {
+            "* "
+        } else {
+            "  "
+        };
+        let mut line = String::from(mark);
+        for value in trial.params.values() {
+            line.push_str(&format!("{:>12} ", value));
+        }
+        line.push_str(&format!(
+            "{:>14} {:>8} {:>10.1}",
+            trial.total_score, trial.failures, trial.mean_ms
+        ));
+        println!("{}", line);
+    }
+}
+
+pub fn write_trials_csv(trials: &[Trial], path: &str) -> io::Result<()> {
+    let Some(first) = trials.first() else {
+        return fs::write(path, "");
+    };
+    let mut csv: Vec<String> = first.params.keys().cloned().collect();
+    csv.extend(["total_score", "failures", "mean_ms"].map(String::from));
+    let mut csv = csv.join(",") + "\n";
+    for trial in trials {
+        let mut row: Vec<String> = trial.params.values().cloned().collect();
+        row.push(trial.total_score.to_string());
+        row.push(trial.failures.to_string());
+        row.push(format!("{:.1}", trial.mean_ms));
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    fs::write(path, csv)
+}
@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::html::generate_html;
+use crate::junit::write_junit;
+use crate::pahcer::write_pahcer;
+use crate::results::{load_results, results_path, save_results, write_csv};
+use crate::visualizer::visualize_selected;
+
+/// Reruns only the visualizer over the solver outputs of the last run and
+/// rewrites the report, keeping the cached scores.
+pub fn vis(config: &Config) {
+    let path = results_path(config);
+    let mut run = match load_results(&path) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Error loading results from {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    fs::create_dir_all(&config.paths.visualizer_dir).ok();
+    let worst = config
+        .visualizer
+        .as_ref()
+        .and_then(|v| v.worst_k)
+        .map(|k| run.objective.worst_seeds(&run.results, k));
+    let results = std::mem::take(&mut run.results);
+    run.results = visualize_selected(results, config, |result| {
+        if !Path::new(&result.output_file).exists() {
+            eprintln!("[WARN] No solver output for {}", result.input_file);
+            return false;
+        }
+        worst
+            .as_ref()
+            .is_none_or(|worst| worst.contains(&result.seed))
+    });
+    let visualized = run
+        .results
+        .iter()
+        .filter(|r| !r.visualizer.is_empty())
+        .count();
+
+    generate_html(&run, None, config);
+    if let Some(csv_output) = &config.paths.csv_output {
+        if let Err(e) = write_csv(&run.results, csv_output) {
+            eprintln!("Error writing CSV file: {}", e);
+        }
+    }
+    if let Some(pahcer_output) = &config.paths.pahcer_output {
+        if let Err(e) = write_pahcer(&run, pahcer_output) {
+            eprintln!("Error writing pahcer results: {}", e);
+        }
+    }
+    if let Some(junit_output) = &config.paths.junit_output {
+        if let Err(e) = write_junit(&run, junit_output) {
+            eprintln!("Error writing JUnit XML: {}", e);
+        }
+    }
+    if let Err(e) = save_results(&path, &run) {
+        eprintln!("Error writing results JSON: {}", e);
+    }
+
+    println!("Visualized {} of {} cases", visualized, run.results.len());
+    println!("Results saved to {}", config.paths.html_output);
+}
@@ -0,0 +1,81 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Component, Path};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::cli::WatchArgs;
+use crate::commands::run::run;
+use crate::config::Config;
+
+/// Wait this long after a change before rerunning, so a burst of writes
+/// (an editor save, a build) triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub fn watch(config: &Config, args: &WatchArgs) {
+    let paths: Vec<String> = if args.paths.is_empty() {
+        [&config.tester.script, &config.tester.solver_script]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    } else {
+        args.paths.clone()
+    };
+    if paths.is_empty() {
+        eprintln!("Error: nothing to watch; set tester.script or pass --path");
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Error starting file watcher: {}", e);
+            return;
+        }
+    };
+    for path in &paths {
+        if let Err(e) = watcher.watch(Path::new(path), RecursiveMode::Recursive) {
+            eprintln!("Error watching {}: {}", path, e);
+            return;
+        }
+        eprintln!("Watching {}", path);
+    }
+
+    run(config, &args.run);
+    loop {
+        // Changes made while the previous run was going on (build artifacts,
+        // outputs) must not retrigger it
+        while rx.try_recv().is_ok() {}
+
+        eprintln!("Waiting for changes... (Ctrl-C to stop)");
+        loop {
+            match rx.recv() {
+                Ok(Ok(event)) if is_relevant(&event) => break,
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("Watch error: {}", e),
+                Err(_) => return,
+            }
+        }
+        thread::sleep(DEBOUNCE);
+        while rx.try_recv().is_ok() {}
+
+        eprintln!("Change detected, rerunning");
+        run(config, &args.run);
+    }
+}
+
+/// Content changes outside build and VCS directories.
+fn is_relevant(event: &notify::Event) -> bool {
+    let kind_matches = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    kind_matches
+        && event.paths.iter().any(|path| {
+            !path
+                .components()
+                .any(|c| matches!(c, Component::Normal(name) if name == "target" || name == ".git"))
+        })
+}
@@ -0,0 +1,982 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+use crate::colors::Rgb;
+use crate::features::FeaturesConfig;
+use crate::gate::FailOn;
+use crate::hooks::HooksConfig;
+use crate::notify::NotifyConfig;
+use crate::progress::log;
+use crate::scoring::ScoringConfig;
+use crate::seeds::SeedFilter;
+use crate::sweep::{ParamValue, SweepConfig};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub paths: PathsConfig,
+    pub tester: TesterConfig,
+    #[serde(default)]
+    pub parallel: Option<ParallelConfig>,
+    #[serde(default)]
+    pub scorer: Option<ScorerConfig>,
+    #[serde(default)]
+    pub visualizer: Option<VisualizerConfig>,
+    #[serde(default)]
+    pub scoring: Option<ScoringConfig>,
+    #[serde(default)]
+    pub features: Option<FeaturesConfig>,
+    #[serde(default)]
+    pub report: Option<ReportConfig>,
+    #[serde(default)]
+    pub run: Option<RunConfig>,
+    #[serde(default)]
+    pub hooks: Option<HooksConfig>,
+    #[serde(default)]
+    pub notify: Option<NotifyConfig>,
+    #[serde(default)]
+    pub sweep: Option<SweepConfig>,
+    /// Solver parameters and their values, filled into `{{param:NAME}}` and
+    /// exported as environment variables of the same name; `run --param`,
+    /// `sweep` and `optimize` override them
+    #[serde(default)]
+    pub params: BTreeMap<String, ParamValue>,
+    /// Named sets of values laid over the rest with `--profile NAME`
+    #[serde(default)]
+    pub profile: BTreeMap<String, toml::Table>,
+}
+
+/// What the command line changes on top of the config file.
+#[derive(Default)]
+pub struct Overrides {
+    /// `[profile.NAME]` to apply
+    pub profile: Option<String>,
+    /// `--set KEY=VALUE` pairs, applied last
+    pub set: Vec<(String, String)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParallelConfig {
+    pub num_threads: Option<usize>,
+    /// Threads for running the visualizer (default: `num_threads`)
+    #[serde(default)]
+    pub vis_threads: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathsConfig {
+    pub input_dir: String,
+    pub output_dir: String,
+    pub visualizer_dir: String,
+    pub html_output: String,
+    #[serde(default)]
+    pub answers_dir: Option<String>,
+    #[serde(default)]
+    pub results_json: Option<String>,
+    #[serde(default)]
+    pub csv_output: Option<String>,
+    #[serde(default)]
+    pub history_db: Option<String>,
+    #[serde(default)]
+    pub trend_output: Option<String>,
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+    /// Best score per seed across all runs, updated after each one; used for
+    /// relative scores and the report's potential column
+    #[serde(default)]
+    pub best_scores: Option<String>,
+    /// Git repository whose commit is recorded with each run (default: the
+    /// one holding the solver script)
+    #[serde(default)]
+    pub solver_repo: Option<String>,
+    /// The results again in pahcer's result file format
+    #[serde(default)]
+    pub pahcer_output: Option<String>,
+    /// JUnit XML with one test case per seed, for CI systems
+    #[serde(default)]
+    pub junit_output: Option<String>,
+    /// Where each run's logs are saved, in a new directory named after its
+    /// start time and process id: `run.log`, and each case's tester stderr
+    /// as `<seed>.stderr.txt` and command line and exit code as
+    /// `<seed>.case.txt`
+    #[serde(default)]
+    pub logs_dir: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TesterConfig {
+    /// Supports `{{script}}`, `{{solver_script}}`, `{{input_file}}`,
+    /// `{{output_file}}`, `{{seed}}`, `{{tools_dir}}`, `{{param:NAME}}` and,
+    /// with `paths.logs_dir`, `{{solver_stderr}}`
+    pub command: String,
+    pub script: Option<String>,
+    pub solver_script: Option<String>,
+    /// Wall-clock limit per case; the tester is killed and the case marked TLE when exceeded
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+    /// Peak memory above which a case is flagged in the log and the report
+    #[serde(default)]
+    pub memory_warning_mb: Option<u64>,
+    /// Regex whose first capture group is the score
+    #[serde(default)]
+    pub score_regex: Option<String>,
+    /// Which tester stream the score is read from
+    #[serde(default)]
+    pub score_source: Option<ScoreSource>,
+    /// Extra named values to capture from the tester output
+    #[serde(default)]
+    pub metrics: Vec<MetricConfig>,
+    /// File the tester writes the solution to, instead of stdout; takes the
+    /// same placeholders as `command`
+    #[serde(default)]
+    pub solution_file: Option<String>,
+    /// Run `command` through `sh -c` (`cmd /C` on Windows) so pipes and
+    /// redirection work
+    #[serde(default)]
+    pub use_shell: bool,
+    /// Environment variables set for the tester; values take the same
+    /// placeholders as `command`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Extra attempts for a case whose tester exits non-zero or prints no score
+    #[serde(default)]
+    pub retries: u32,
+    /// Only run these seeds, e.g. "0-49,100"; overridden by `--seeds`
+    #[serde(default)]
+    pub seeds: Option<SeedFilter>,
+    /// How the tester gets the input and gives back the solution
+    #[serde(default)]
+    pub io_mode: IoMode,
+    /// `command` is the solver, run by the problem's interactive tester, which
+    /// reads the input on stdin and prints the solver's answer on stdout
+    #[serde(default)]
+    pub interactive: bool,
+    /// The interactive tester, given the solver command as its arguments;
+    /// takes the same placeholders as `command`
+    #[serde(default)]
+    pub interactive_tester: Option<String>,
+}
+
+/// The official tools' interactive tester, once built with `cargo build -r`.
+pub const DEFAULT_INTERACTIVE_TESTER: &str = "{{tools_dir}}/target/release/tester";
+
+impl TesterConfig {
+    /// Defaults to stderr, or to both streams when a custom `score_regex` is set.
+    pub fn score_source(&self) -> ScoreSource {
+        self.score_source.unwrap_or(if self.score_regex.is_some() {
+            ScoreSource::Both
+        } else {
+            ScoreSource::Stderr
+        })
+    }
+
+    /// The interactive tester command, if `interactive` is set.
+    pub fn interactive_tester(&self) -> Option<&str> {
+        self.interactive.then(|| {
+            self.interactive_tester
+                .as_deref()
+                .unwrap_or(DEFAULT_INTERACTIVE_TESTER)
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricConfig {
+    pub name: String,
+    /// Regex whose first capture group is the value
+    pub regex: String,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreSource {
+    Stderr,
+    Stdout,
+    /// stderr first, then stdout
+    Both,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoMode {
+    /// The input is written to stdin and the solution read from stdout
+    #[default]
+    Stdin,
+    /// The tester reads `{{input_file}}` and writes the solution to `{{output_file}}`
+    Files,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScorerConfig {
+    pub command: String,
+    pub working_dir: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunConfig {
+    /// Stop the run once this many seeds have failed (not AC)
+    #[serde(default)]
+    pub max_failures: Option<usize>,
+    /// Exit with a non-zero status when any of these happen
+    #[serde(default)]
+    pub fail_on: Vec<FailOn>,
+    /// Losses up to this percentage of the baseline score do not count as
+    /// a regression, for the total and for each seed
+    #[serde(default)]
+    pub max_regression_percent: Option<f64>,
+    /// Also fail "regression" when more seeds than this regressed
+    #[serde(default)]
+    pub max_regressed_seeds: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportConfig {
+    /// Open the report in the default browser once it has been written
+    #[serde(default)]
+    pub open: bool,
+    /// Open visualizations in an overlay on the report, with previous/next
+    /// navigation, instead of a new tab
+    #[serde(default)]
+    pub preview: bool,
+    /// Tera template to render the report with instead of the built-in one
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Color score cells on a gradient from the worst to the best seed
+    #[serde(default)]
+    pub heatmap: bool,
+    /// Stops of the heatmap gradient, worst first
+    #[serde(default = "default_heatmap_colors")]
+    pub heatmap_colors: Vec<Rgb>,
+    /// Where to write a short markdown summary of the run
+    #[serde(default)]
+    pub markdown_output: Option<String>,
+    /// Open the report with log10 scores shown and the charts on a log
+    /// scale; a button in the report switches back and forth
+    #[serde(default)]
+    pub log_scale: bool,
+}
+
+fn default_heatmap_colors() -> Vec<Rgb> {
+    ["#f8696b", "#ffeb84", "#63be7b"]
+        .iter()
+        .map(|c| c.parse().unwrap())
+        .collect()
+}
+
+/// Color scheme of the report; `auto` follows the browser's preference.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    Auto,
+}
+
+impl Config {
+    pub fn open_report(&self) -> bool {
+        self.report.as_ref().is_some_and(|r| r.open)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VisualizerConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Supports `{{input_file}}`, `{{output_file}}` and `{{vis_output}}`
+    pub command: Option<String>,
+    /// Where the command runs; without `{{vis_output}}` visualizations then
+    /// run one at a time, as they all write `output` there
+    pub working_dir: Option<String>,
+    /// File the visualizer writes, relative to `working_dir`, when the
+    /// command does not use `{{vis_output}}` (default: vis.html)
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Only visualize this many of the worst-scoring seeds
+    #[serde(default)]
+    pub worst_k: Option<usize>,
+    /// Capture a PNG of each visualization to show in the report
+    #[serde(default)]
+    pub thumbnails: bool,
+    /// Supports `{{vis_file}}` and `{{thumbnail_file}}` (default: headless
+    /// Chromium)
+    #[serde(default)]
+    pub thumbnail_command: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Config {
+    /// The parameters as the solver sees them.
+    pub fn param_values(&self) -> BTreeMap<String, String> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.clone(), value.to_string()))
+            .collect()
+    }
+
+    /// A copy with `overrides` replacing or adding parameters.
+    pub fn with_params<'a>(
+        &self,
+        overrides: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Config {
+        let mut config = self.clone();
+        config.params.extend(
+            overrides
+                .into_iter()
+                .map(|(name, value)| (name.clone(), ParamValue::Str(value.clone()))),
+        );
+        config
+    }
+}
+
+/// Prefix of the environment variables overriding config values.
+const ENV_PREFIX: &str = "AHC_VIS_";
+
+/// The top-level tables, for telling the table from the key in environment
+/// variable names.
+const SECTIONS: [&str; 13] = [
+    "paths",
+    "tester",
+    "parallel",
+    "scorer",
+    "visualizer",
+    "scoring",
+    "features",
+    "report",
+    "run",
+    "hooks",
+    "notify",
+    "sweep",
+    "params",
+];
+
+/// Reads the config at `path` with the selected profile, the `AHC_VIS_*`
+/// environment variables and then the `--set` overrides applied on top.
+pub fn load_config(path: &str, overrides: &Overrides) -> io::Result<Config> {
+    let mut set = env_overrides();
+    set.extend_from_slice(&overrides.set);
+    let config_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    read_extended(Path::new(path), &mut Vec::new())
+        .and_then(|config_str| apply_overrides(&config_str, overrides.profile.as_deref(), &set))
+        .and_then(|config_str| {
+            parse_config(&config_str).map_err(|e| {
+                if converted(Path::new(path)) {
+                    let message = format!("{}\n(in the config converted to TOML)", e);
+                    io::Error::new(e.kind(), message)
+                } else {
+                    e
+                }
+            })
+        })
+        .and_then(|mut config| {
+            resolve_paths(&mut config.paths, config_dir)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            Ok(config)
+        })
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))
+}
+
+/// Expands `~` and `$VAR` in the `[paths]` entries and makes relative ones
+/// relative to `config_dir`, so the config works from any directory.
+fn resolve_paths(paths: &mut PathsConfig, config_dir: &Path) -> Result<(), String> {
+    let required = [
+        ("input_dir", &mut paths.input_dir),
+        ("output_dir", &mut paths.output_dir),
+        ("visualizer_dir", &mut paths.visualizer_dir),
+        ("html_output", &mut paths.html_output),
+    ];
+    let optional = [
+        ("answers_dir", &mut paths.answers_dir),
+        ("results_json", &mut paths.results_json),
+        ("csv_output", &mut paths.csv_output),
+        ("history_db", &mut paths.history_db),
+        ("trend_output", &mut paths.trend_output),
+        ("cache_dir", &mut paths.cache_dir),
+        ("best_scores", &mut paths.best_scores),
+        ("solver_repo", &mut paths.solver_repo),
+        ("pahcer_output", &mut paths.pahcer_output),
+        ("junit_output", &mut paths.junit_output),
+        ("logs_dir", &mut paths.logs_dir),
+    ];
+    let optional = optional
+        .into_iter()
+        .filter_map(|(name, path)| Some((name, path.as_mut()?)));
+    for (name, path) in required.into_iter().chain(optional) {
+        let expanded = expand_path(path).map_err(|e| format!("paths.{}: {}", name, e))?;
+        // "./config.toml" leaves the paths as written
+        *path = if Path::new(&expanded).is_relative()
+            && !config_dir.as_os_str().is_empty()
+            && config_dir != Path::new(".")
+        {
+            config_dir.join(&expanded).to_string_lossy().to_string()
+        } else {
+            expanded
+        };
+    }
+    Ok(())
+}
+
+/// Replaces a leading `~` with the home directory and `$VAR` or `${VAR}`
+/// with the variable's value.
+fn expand_path(path: &str) -> Result<String, String> {
+    let path = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => {
+            let home = env::var("HOME")
+                .or_else(|_| env::var("USERPROFILE"))
+                .map_err(|_| "~ needs HOME to be set".to_string())?;
+            format!("{}{}", home, rest)
+        }
+        _ => path.to_string(),
+    };
+    let variable = Regex::new(r"\$(?:\{(\w+)\}|(\w+))").unwrap();
+    let mut unset = None;
+    let expanded = variable.replace_all(&path, |caps: &regex::Captures| {
+        let name = caps.get(1).or(caps.get(2)).unwrap().as_str();
+        env::var(name).unwrap_or_else(|_| {
+            unset.get_or_insert_with(|| name.to_string());
+            String::new()
+        })
+    });
+    match unset {
+        Some(name) => Err(format!("${} is not set", name)),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+/// The config at `path` laid over the file its `extends` names, relative
+/// to it, and so on up the chain. `chain` holds the files being read, to
+/// catch a file extending itself.
+fn read_extended(path: &Path, chain: &mut Vec<PathBuf>) -> io::Result<String> {
+    let config_str = read_as_toml(path)?;
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut doc: DocumentMut = config_str.parse().map_err(|e| invalid(format!("{}", e)))?;
+    let Some(extends) = doc.remove("extends") else {
+        // Unchanged, so errors point at the lines of the file as written
+        return Ok(config_str);
+    };
+    let base = extends
+        .as_str()
+        .ok_or_else(|| invalid("extends must be the path of another config".to_string()))?;
+    let base_path = path.parent().unwrap_or(Path::new("")).join(base);
+    chain.push(fs::canonicalize(path)?);
+    if fs::canonicalize(&base_path).is_ok_and(|base| chain.contains(&base)) {
+        return Err(invalid(format!("{} extends itself", base_path.display())));
+    }
+    let base_str = read_extended(&base_path, chain)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", base_path.display(), e)))?;
+    let mut base_doc: DocumentMut = base_str.parse().map_err(|e| invalid(format!("{}", e)))?;
+    rebase_paths(
+        &mut base_doc,
+        Path::new(base).parent().unwrap_or(Path::new("")),
+    );
+    merge(base_doc.as_table_mut(), doc.as_table());
+    Ok(base_doc.to_string())
+}
+
+/// Prefixes the relative `[paths]` of a base config with `dir`, the base's
+/// directory as seen from the config extending it, so they keep pointing
+/// where the base meant once `resolve_paths` takes them from the top-level
+/// config's directory.
+fn rebase_paths(doc: &mut DocumentMut, dir: &Path) {
+    if dir.as_os_str().is_empty() {
+        return;
+    }
+    let Some(paths) = doc.get_mut("paths").and_then(Item::as_table_like_mut) else {
+        return;
+    };
+    for (_, item) in paths.iter_mut() {
+        // Left for `resolve_paths` to report if it does not expand
+        let Some(Ok(expanded)) = item.as_str().map(expand_path) else {
+            continue;
+        };
+        if Path::new(&expanded).is_relative() {
+            *item = toml_edit::value(dir.join(&expanded).to_string_lossy().as_ref());
+        }
+    }
+}
+
+/// The config file at `path` as TOML. `.json`, `.yaml` and `.yml` files are
+/// converted, so every format gets `extends`, profiles and overrides.
+fn read_as_toml(path: &Path) -> io::Result<String> {
+    let config_str = fs::read_to_string(path)?;
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let value: serde_json::Value = match extension(path) {
+        Some("json") => serde_json::from_str(&config_str).map_err(|e| invalid(e.to_string()))?,
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(&config_str).map_err(|e| invalid(e.to_string()))?
+        }
+        _ => return Ok(config_str),
+    };
+    toml::Value::try_from(without_nulls(value))
+        .ok()
+        .and_then(|value| toml::to_string(&value).ok())
+        .ok_or_else(|| invalid("the config must be an object of tables".to_string()))
+}
+
+fn extension(path: &Path) -> Option<&str> {
+    path.extension().and_then(|ext| ext.to_str())
+}
+
+/// Whether the file is read through `read_as_toml`'s conversion.
+fn converted(path: &Path) -> bool {
+    matches!(extension(path), Some("json" | "yaml" | "yml"))
+}
+
+/// TOML has no null; an unset value is a missing key.
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(without_nulls)
+            .collect(),
+        value => value,
+    }
+}
+
+/// Tables whose keys are names the user chose, kept in their case when
+/// set from the environment; every other key is lowercase.
+const USER_NAMED: [&str; 4] = ["params", "tester.env", "sweep.params", "sweep.ranges"];
+
+/// `AHC_VIS_PARALLEL_NUM_THREADS=4` sets `parallel.num_threads`: the name
+/// starts with a table, the rest is the key. Deeper keys separate every
+/// level with `__`, as in `AHC_VIS_TESTER__ENV__RUST_LOG`. Variables naming
+/// no table are someone else's and skipped with a warning.
+fn env_overrides() -> Vec<(String, String)> {
+    let mut overrides = Vec::new();
+    for (name, value) in env::vars() {
+        let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let parts: Vec<&str> = if rest.contains("__") {
+            rest.split("__").collect()
+        } else {
+            let lower = rest.to_lowercase();
+            let Some(section) = SECTIONS
+                .iter()
+                .find(|section| lower.starts_with(&format!("{}_", section)))
+            else {
+                log!(
+                    "[WARN] Ignoring {}: not a config table, as in {}PARALLEL_NUM_THREADS",
+                    name,
+                    ENV_PREFIX
+                );
+                continue;
+            };
+            vec![&rest[..section.len()], &rest[section.len() + 1..]]
+        };
+        let mut key = String::new();
+        for part in parts {
+            let part = if USER_NAMED.contains(&key.as_str()) {
+                part.to_string()
+            } else {
+                part.to_lowercase()
+            };
+            if !key.is_empty() {
+                key.push('.');
+            }
+            key.push_str(&part);
+        }
+        overrides.push((key, value));
+    }
+    // Environment order is arbitrary; keep the result stable
+    overrides.sort();
+    overrides
+}
+
+/// Sets each dotted `KEY` to `VALUE`, read as TOML when it parses (`4`,
+/// `true`, `[1, 2]`) and the config takes it, and as a string otherwise. Tables on the way are
+/// created as needed; the rest of the file keeps its layout, so errors
+/// still point at the right lines.
+fn apply_overrides(
+    config_str: &str,
+    profile: Option<&str>,
+    overrides: &[(String, String)],
+) -> io::Result<String> {
+    if profile.is_none() && overrides.is_empty() {
+        return Ok(config_str.to_string());
+    }
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut doc: DocumentMut = config_str.parse().map_err(|e| invalid(format!("{}", e)))?;
+    if let Some(name) = profile {
+        let values = doc
+            .get("profile")
+            .and_then(|profiles| profiles.get(name))
+            .filter(|values| values.is_table_like())
+            .cloned()
+            .ok_or_else(|| invalid(format!("no [profile.{}] in the config", name)))?;
+        if let Some(values) = values.as_table_like() {
+            merge(doc.as_table_mut(), values);
+        }
+    }
+    for (key, value) in overrides {
+        let parts: Vec<&str> = key.split('.').map(str::trim).collect();
+        let Some((last, parents)) = parts.split_last().filter(|_| !parts.contains(&"")) else {
+            return Err(invalid(format!("cannot set {}: invalid key", key)));
+        };
+        let string = Value::from(value.as_str());
+        let parsed = value.parse::<Value>().unwrap_or_else(|_| string.clone());
+        let typed = !parsed.is_str();
+        override_table(&mut doc, key, parents)?.insert(last, Item::Value(parsed.clone()));
+        // `7` is a string too, as `tester.seeds` or a path may want it
+        if typed && toml::from_str::<Config>(&doc.to_string()).is_err() {
+            override_table(&mut doc, key, parents)?.insert(last, Item::Value(string));
+            if toml::from_str::<Config>(&doc.to_string()).is_err() {
+                // Neither fits; the typed value's error is the one to show
+                override_table(&mut doc, key, parents)?.insert(last, Item::Value(parsed));
+            }
+        }
+    }
+    Ok(doc.to_string())
+}
+
+/// The table an override of `key` goes in, following `parents` and creating
+/// the tables on the way as needed.
+fn override_table<'a>(
+    doc: &'a mut DocumentMut,
+    key: &str,
+    parents: &[&str],
+) -> io::Result<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for part in parents {
+        let mut implicit = Table::new();
+        implicit.set_implicit(true);
+        table = table
+            .entry(part)
+            .or_insert(Item::Table(implicit))
+            .as_table_like_mut()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("cannot set {}: {} is not a table", key, part),
+                )
+            })?;
+    }
+    Ok(table)
+}
+
+/// Lays `overlay` over `table`, merging tables present in both.
+fn merge(table: &mut dyn TableLike, overlay: &dyn TableLike) {
+    for (key, item) in overlay.iter() {
+        let existing = table.get_mut(key).and_then(Item::as_table_like_mut);
+        match (existing, item.as_table_like()) {
+            (Some(existing), Some(overlay)) => merge(existing, overlay),
+            _ => {
+                table.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Parses and validates a config. Parse errors quote the offending line;
+/// validation errors name the field and the line it is set on.
+pub fn parse_config(config_str: &str) -> io::Result<Config> {
+    let config: Config = toml::from_str(config_str)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    validate_config(&config).map_err(|(field, message)| {
+        let message = match field_line(config_str, &field) {
+            Some((number, line)) => {
+                format!("{}\n  at line {}: {}", message, number, line.trim())
+            }
+            None => message,
+        };
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    })?;
+    Ok(config)
+}
+
+/// The 1-based number and text of the line setting `field` (a dotted path
+/// such as `tester.command`), found by following the table headers.
+fn field_line<'a>(config_str: &'a str, field: &str) -> Option<(usize, &'a str)> {
+    let unquote = |key: &str| {
+        key.split('.')
+            .map(|part| part.trim().trim_matches(|c| c == '"' || c == '\''))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    let mut table = String::new();
+    for (i, line) in config_str.lines().enumerate() {
+        let trimmed = line.trim();
+        let path = if let Some(header) = trimmed.strip_prefix('[') {
+            table = unquote(header.trim_start_matches('[').split(']').next()?);
+            table.clone()
+        } else if let Some((key, _)) = trimmed.split_once('=') {
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            match table.as_str() {
+                "" => unquote(key),
+                table => format!("{}.{}", table, unquote(key)),
+            }
+        } else {
+            continue;
+        };
+        if path == field {
+            return Some((i + 1, line));
+        }
+    }
+    None
+}
+
+/// The placeholders the tester's command, env and solution file can use.
+pub fn tester_placeholders(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = ["input_file", "output_file", "seed", "tools_dir"]
+        .map(String::from)
+        .to_vec();
+    if config.tester.script.is_some() {
+        names.push("script".to_string());
+    }
+    if config.tester.solver_script.is_some() {
+        names.push("solver_script".to_string());
+    }
+    if config.paths.logs_dir.is_some() {
+        names.push("solver_stderr".to_string());
+    }
+    // Sweeps and `optimize` fill in their own parameters
+    let swept = config
+        .sweep
+        .iter()
+        .flat_map(|s| s.params.keys().chain(s.ranges.keys()));
+    let mut params: Vec<&String> = config.params.keys().chain(swept).collect();
+    params.sort();
+    params.dedup();
+    names.extend(params.into_iter().map(|name| format!("param:{}", name)));
+    names
+}
+
+/// The `{{name}}`s in `template` whose name is not in `available`.
+pub fn unknown_placeholders<'a>(template: &'a str, available: &[String]) -> Vec<&'a str> {
+    let placeholder = Regex::new(r"\{\{([^{}]*)\}\}").unwrap();
+    placeholder
+        .captures_iter(template)
+        .map(|caps| caps.get(0).unwrap().as_str())
+        .filter(|used| {
+            let name = &used[2..used.len() - 2];
+            !available.iter().any(|available| available == name)
+        })
+        .collect()
+}
+
+/// Checks what serde cannot. Errors carry the field at fault, for
+/// `parse_config` to locate.
+fn validate_config(config: &Config) -> Result<(), (String, String)> {
+    let invalid = |field: &str, message: String| Err((field.to_string(), message));
+    let commands = [
+        (
+            "tester.command",
+            Some(&config.tester.command).filter(|_| !config.tester.use_shell),
+        ),
+        (
+            "tester.interactive_tester",
+            config.tester.interactive_tester.as_ref(),
+        ),
+        ("scorer.command", config.scorer.as_ref().map(|s| &s.command)),
+        (
+            "visualizer.command",
+            config.visualizer.as_ref().and_then(|v| v.command.as_ref()),
+        ),
+        (
+            "visualizer.thumbnail_command",
+            config
+                .visualizer
+                .as_ref()
+                .and_then(|v| v.thumbnail_command.as_ref()),
+        ),
+    ];
+    for (name, command) in commands {
+        if let Some(command) = command {
+            if let Err(e) = shell_words::split(command) {
+                return invalid(name, format!("invalid {}: {}", name, e));
+            }
+        }
+    }
+
+    let available = tester_placeholders(config);
+    let templates = std::iter::once(("tester.command".to_string(), &config.tester.command))
+        .chain(
+            config
+                .tester
+                .env
+                .iter()
+                .map(|(name, value)| (format!("tester.env.{}", name), value)),
+        )
+        .chain(
+            config
+                .tester
+                .solution_file
+                .iter()
+                .map(|file| ("tester.solution_file".to_string(), file)),
+        )
+        .chain(
+            config
+                .tester
+                .interactive_tester
+                .iter()
+                .map(|tester| ("tester.interactive_tester".to_string(), tester)),
+        );
+    for (name, template) in templates {
+        let unknown = unknown_placeholders(template, &available);
+        if !unknown.is_empty() {
+            let available: Vec<String> = available
+                .iter()
+                .map(|name| format!("{{{{{}}}}}", name))
+                .collect();
+            return invalid(
+                &name,
+                format!(
+                    "{} uses {}, which nothing fills in; available: {} ({{{{script}}}}, \
+                     {{{{solver_script}}}} and {{{{solver_stderr}}}} need tester.script, \
+                     tester.solver_script and paths.logs_dir; {{{{param:NAME}}}} needs NAME \
+                     in [params] or [sweep])",
+                    name,
+                    unknown.join(", "),
+                    available.join(", ")
+                ),
+            );
+        }
+    }
+
+    if config.tester.io_mode == IoMode::Files {
+        let uses = |placeholder: &str| {
+            std::iter::once(&config.tester.command)
+                .chain(config.tester.env.values())
+                .chain(config.tester.interactive_tester.iter())
+                .any(|template| template.contains(placeholder))
+        };
+        let mut missing = Vec::new();
+        if !uses("{{input_file}}") {
+            missing.push("{{input_file}}");
+        }
+        if !uses("{{output_file}}") && config.tester.solution_file.is_none() {
+            missing.push("{{output_file}}");
+        }
+        if !missing.is_empty() {
+            return invalid(
+                "tester.io_mode",
+                format!(
+                    "tester.io_mode = \"files\" needs {} in tester.command or tester.env \
+                     (or tester.solution_file for the output)",
+                    missing.join(" and ")
+                ),
+            );
+        }
+    }
+
+    if let Some(ref pattern) = config.tester.score_regex {
+        match Regex::new(pattern) {
+            Err(e) => {
+                return invalid(
+                    "tester.score_regex",
+                    format!("invalid tester.score_regex: {}", e),
+                )
+            }
+            Ok(re) if re.captures_len() < 2 => {
+                return invalid(
+                    "tester.score_regex",
+                    "tester.score_regex must contain a capture group for the score".to_string(),
+                )
+            }
+            Ok(_) => {}
+        }
+    }
+    for metric in &config.tester.metrics {
+        match Regex::new(&metric.regex) {
+            Err(e) => {
+                return invalid(
+                    "tester.metrics",
+                    format!("invalid regex for metric {}: {}", metric.name, e),
+                )
+            }
+            Ok(re) if re.captures_len() < 2 => {
+                return invalid(
+                    "tester.metrics",
+                    format!(
+                        "regex for metric {} must contain a capture group",
+                        metric.name
+                    ),
+                )
+            }
+            Ok(_) => {}
+        }
+    }
+    let ranges = config.sweep.iter().flat_map(|s| &s.ranges);
+    for (name, range) in ranges {
+        if let Some(problem) = range.problem() {
+            let field = format!("sweep.ranges.{}", name);
+            return invalid(&field, format!("invalid {}: {}", field, problem));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[paths]
+input_dir = "in"
+output_dir = "out"
+visualizer_dir = "vis"
+html_output = "index.html"
+
+[tester]
+command = "./tester"
+"#;
+
+    fn set(key: &str, value: &str) -> Config {
+        let overrides = [(key.to_string(), value.to_string())];
+        let config_str = apply_overrides(CONFIG, None, &overrides).unwrap();
+        parse_config(&config_str).unwrap()
+    }
+
+    #[test]
+    fn numeric_override_of_a_string_field_stays_a_string() {
+        assert!(set("tester.seeds", "7").tester.seeds.is_some());
+        assert_eq!(set("paths.output_dir", "2024").paths.output_dir, "2024");
+    }
+
+    #[test]
+    fn base_config_paths_stay_relative_to_the_base() {
+        let mut doc: DocumentMut =
+            "[paths]\ninput_dir = \"in\"\nhtml_output = \"/abs/index.html\"\n"
+                .parse()
+                .unwrap();
+        rebase_paths(&mut doc, Path::new("../shared"));
+        assert_eq!(doc["paths"]["input_dir"].as_str(), Some("../shared/in"));
+        assert_eq!(
+            doc["paths"]["html_output"].as_str(),
+            Some("/abs/index.html")
+        );
+    }
+
+    #[test]
+    fn numeric_override_of_a_number_field_is_a_number() {
+        let config = set("parallel.num_threads", "4");
+        assert_eq!(config.parallel.and_then(|p| p.num_threads), Some(4));
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeaturesConfig {
+    /// Names for the leading whitespace-separated values of each input file,
+    /// in order. Use "_" to skip a value.
+    pub names: Vec<String>,
+    /// Feature to group seeds by in the report's summary section
+    #[serde(default)]
+    pub group_by: Option<String>,
+    /// Number of value ranges for `group_by`
+    #[serde(default = "default_bucket_count")]
+    pub bucket_count: usize,
+}
+
+fn default_bucket_count() -> usize {
+    5
+}
+
+impl FeaturesConfig {
+    /// Feature names in column order, without skipped values.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str).filter(|&n| n != "_")
+    }
+}
+
+/// Reads the configured features from the start of an input file. Values
+/// that are missing or not numeric are left out.
+pub fn parse_features(input: &str, config: &FeaturesConfig) -> BTreeMap<String, f64> {
+    config
+        .names
+        .iter()
+        .zip(input.split_whitespace())
+        .filter(|(name, _)| name.as_str() != "_")
+        .filter_map(|(name, token)| Some((name.clone(), token.parse::<f64>().ok()?)))
+        .collect()
+}
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::RunConfig;
+use crate::progress::log;
+use crate::results::{Result, RunResults, Verdict};
+use crate::scoring::Objective;
+use crate::stats::wilcoxon_signed_rank;
+
+/// A condition in `run.fail_on` that makes `run` exit with a non-zero status.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    /// The seeds run score worse than in the baseline, beyond the tolerance
+    /// of `max_regression_percent` and `max_regressed_seeds`
+    Regression,
+    /// A seed ended in RE
+    Crash,
+    /// A seed ended in WA
+    Wa,
+    /// A seed ended in TLE
+    Tle,
+}
+
+/// Checks the finished run against `run.fail_on`, returning why it failed.
+pub fn check(
+    run_config: &RunConfig,
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    for condition in &run_config.fail_on {
+        let verdict = match condition {
+            FailOn::Regression => {
+                match baseline {
+                    Some(baseline) => failures.extend(regression(run_config, run, baseline)),
+                    None => log!(
+                        "[WARN] fail_on = \"regression\" needs a baseline; \
+                         no earlier run to compare with"
+                    ),
+                }
+                continue;
+            }
+            FailOn::Crash => Verdict::Re,
+            FailOn::Wa => Verdict::Wa,
+            FailOn::Tle => Verdict::Tle,
+        };
+        let seeds: Vec<String> = run
+            .results
+            .iter()
+            .filter(|r| r.verdict == verdict)
+            .map(|r| r.seed.to_string())
+            .collect();
+        if !seeds.is_empty() {
+            failures.push(format!(
+                "{} on seeds {}",
+                verdict.as_str(),
+                seeds.join(", ")
+            ));
+        }
+    }
+    failures
+}
+
+/// Compares only the seeds both runs have, so partial and sampled runs are
+/// judged fairly, combined as for the headline score (`scoring.aggregate`
+/// and `scoring.weights`). A loss within `max_regression_percent` of the
+/// baseline score is noise, both for the total and for single seeds; more
+/// than `max_regressed_seeds` seeds losing beyond it fails too.
+fn regression(run_config: &RunConfig, run: &RunResults, baseline: &RunResults) -> Vec<String> {
+    let baseline_scores: HashMap<usize, i64> =
+        baseline.results.iter().map(|r| (r.seed, r.score)).collect();
+    let tolerance = run_config.max_regression_percent.unwrap_or(0.0);
+    let beyond_tolerance =
+        |gain: i64, base: i64| gain < 0 && -(gain as f64) > base.abs() as f64 * tolerance / 100.0;
+
+    let (mut current, mut previous) = (0i64, 0i64);
+    let (mut shared, mut shared_baseline) = (Vec::new(), Vec::new());
+    let mut regressed: Vec<(usize, i64, i64, i64)> = Vec::new();
+    for result in &run.results {
+        if let Some(&base) = baseline_scores.get(&result.seed) {
+            current = current.saturating_add(result.score);
+            previous = previous.saturating_add(base);
+            shared.push(result.clone());
+            shared_baseline.push(Result {
+                score: base,
+                ..result.clone()
+            });
+            let gain = run.objective.gain(result.score.saturating_sub(base));
+            if beyond_tolerance(gain, base) {
+                regressed.push((result.seed, base, result.score, gain));
+            }
+        }
+    }
+    regressed.sort_by_key(|&(_, _, _, gain)| gain);
+    let seed_list = || {
+        regressed
+            .iter()
+            .map(|&(seed, base, score, _)| {
+                format!(
+                    "{} ({} -> {}, {:+})",
+                    seed,
+                    base,
+                    score,
+                    score.saturating_sub(base)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut failures = Vec::new();
+    match (run.combine(&shared), run.combine(&shared_baseline)) {
+        (Some(current), Some(previous)) => {
+            let delta = current - previous;
+            let gain = match run.objective {
+                Objective::Max => delta,
+                Objective::Min => -delta,
+            };
+            if gain < 0.0 && -gain > previous.abs() * tolerance / 100.0 {
+                let precision = run.precision();
+                failures.push(format!(
+                    "{} score regressed by {:+.*} against the baseline ({:.*} -> {:.*}); \
+                     regressed seeds: {}",
+                    run.score_label(),
+                    precision,
+                    delta,
+                    precision,
+                    previous,
+                    precision,
+                    current,
+                    seed_list()
+                ));
+            }
+        }
+        _ => {
+            let delta = current.saturating_sub(previous);
+            if beyond_tolerance(run.objective.gain(delta), previous) {
+                failures.push(format!(
+                    "score regressed by {:+} against the baseline ({} -> {}); regressed seeds: {}",
+                    delta,
+                    previous,
+                    current,
+                    seed_list()
+                ));
+            }
+        }
+    }
+    if let Some(max_seeds) = run_config.max_regressed_seeds {
+        if regressed.len() > max_seeds {
+            failures.push(format!(
+                "{} seeds regressed, more than max_regressed_seeds = {}: {}",
+                regressed.len(),
+                max_seeds,
+                seed_list()
+            ));
+        }
+    }
+    failures
+}
+
+/// Fewest seeds compared with the baseline before `run --early-stop` may
+/// stop a run.
+const EARLY_STOP_MIN_SEEDS: usize = 10;
+
+/// Watches the per-seed gains against the baseline as results come in, for
+/// `run --early-stop`. The test is repeated after every seed, which makes a
+/// false alarm likelier than its p-value says; the small default threshold
+/// and the minimum seed count keep that rare.
+pub struct EarlyStop {
+    baseline: HashMap<usize, i64>,
+    objective: Objective,
+    threshold: f64,
+    gains: Vec<i64>,
+}
+
+impl EarlyStop {
+    pub fn new(baseline: &RunResults, objective: Objective, threshold: f64) -> Self {
+        EarlyStop {
+            baseline: baseline.results.iter().map(|r| (r.seed, r.score)).collect(),
+            objective,
+            threshold,
+            gains: Vec::new(),
+        }
+    }
+
+    /// Records `result`, returning why the run should stop once the seeds so
+    /// far are significantly worse than in the baseline: most lose, and the
+    /// paired signed-rank test puts the p-value below the threshold.
+    pub fn push(&mut self, result: &Result) -> Option<String> {
+        let &base = self.baseline.get(&result.seed)?;
+        self.gains
+            .push(self.objective.gain(result.score.saturating_sub(base)));
+        if self.gains.len() < EARLY_STOP_MIN_SEEDS {
+            return None;
+        }
+        let losses = self.gains.iter().filter(|&&gain| gain < 0).count();
+        let wins = self.gains.iter().filter(|&&gain| gain > 0).count();
+        let p = wilcoxon_signed_rank(&self.gains)?;
+        (losses > wins && p < self.threshold).then(|| {
+            let p = if p < 0.0001 {
+                "< 0.0001".to_string()
+            } else {
+                format!("= {:.4}", p)
+            };
+            format!(
+                "{} of {} seeds scored worse than in the baseline (signed-rank p {})",
+                losses,
+                self.gains.len(),
+                p
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Aggregate;
+
+    fn run(scores: &[i64]) -> RunResults {
+        let results = scores
+            .iter()
+            .enumerate()
+            .map(|(seed, &score)| Result {
+                score,
+                ..Result::empty(&format!("in/{:04}.txt", seed), "")
+            })
+            .collect();
+        let mut run = RunResults::new(String::new(), scores.iter().sum(), results);
+        run.aggregate = Aggregate::LogSum;
+        run
+    }
+
+    #[test]
+    fn regression_follows_the_aggregate() {
+        let run_config = RunConfig {
+            max_failures: None,
+            fail_on: vec![FailOn::Regression],
+            max_regression_percent: None,
+            max_regressed_seeds: None,
+        };
+        // The sum grows, but the log sum falls
+        let baseline = run(&[1000, 1000]);
+        let current = run(&[10, 10000]);
+        assert!(!check(&run_config, &current, Some(&baseline)).is_empty());
+        let mut current = current;
+        current.aggregate = Aggregate::Sum;
+        assert!(check(&run_config, &current, Some(&baseline)).is_empty());
+    }
+}
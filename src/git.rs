@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::Config;
+
+/// The state of the solver's git repository when a run started.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GitInfo {
+    pub commit: String,
+    /// `HEAD` when detached
+    pub branch: String,
+    /// Tracked files had uncommitted changes
+    pub dirty: bool,
+}
+
+impl GitInfo {
+    /// Short form for headers, e.g. `1a2b3c4 (main, dirty)`.
+    pub fn describe(&self) -> String {
+        let short = &self.commit[..self.commit.len().min(7)];
+        if self.dirty {
+            format!("{} ({}, dirty)", short, self.branch)
+        } else {
+            format!("{} ({})", short, self.branch)
+        }
+    }
+}
+
+/// Reads the repository of `paths.solver_repo`, or else the one holding
+/// the solver script (or the current directory). `None` outside a
+/// repository or without git.
+pub fn git_info(config: &Config) -> Option<GitInfo> {
+    let dir = match config.paths.solver_repo {
+        Some(ref dir) => dir.clone(),
+        None => config
+            .tester
+            .solver_script
+            .as_ref()
+            .or(config.tester.script.as_ref())
+            .map(Path::new)
+            .and_then(|script| {
+                if script.is_dir() {
+                    Some(script)
+                } else {
+                    script.parent()
+                }
+            })
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| ".".to_string()),
+    };
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&dir)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    Some(GitInfo {
+        commit: git(&["rev-parse", "HEAD"])?,
+        branch: git(&["rev-parse", "--abbrev-ref", "HEAD"])?,
+        // Untracked files are usually outputs and build artifacts
+        dirty: !git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty(),
+    })
+}
@@ -0,0 +1,287 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::results::{Result, RunResults, Verdict};
+use crate::runner::format_score;
+use crate::scoring::Aggregate;
+
+/// One row of the runs table.
+pub struct RunSummary {
+    pub id: i64,
+    pub timestamp: String,
+    pub total_score: i64,
+    pub num_cases: usize,
+    pub comment: Option<String>,
+    pub tags: Vec<String>,
+    /// The run's directory under `paths.logs_dir`
+    pub logs_dir: Option<String>,
+    /// Ran only a sample or subset of the seeds
+    pub partial: bool,
+}
+
+/// Every run is recorded here so that past results can be compared and
+/// plotted without re-running the solver.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp   TEXT NOT NULL,
+                total_score INTEGER NOT NULL,
+                num_cases   INTEGER NOT NULL,
+                config      TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS cases (
+                run_id      INTEGER NOT NULL REFERENCES runs(id),
+                seed        INTEGER NOT NULL,
+                input_file  TEXT NOT NULL,
+                score       INTEGER NOT NULL,
+                elapsed_ms  INTEGER NOT NULL,
+                PRIMARY KEY (run_id, seed)
+            );",
+        )?;
+        add_column_if_missing(&conn, "cases", "verdict", "TEXT NOT NULL DEFAULT 'AC'")?;
+        add_column_if_missing(&conn, "runs", "comment", "TEXT")?;
+        // Comma-separated
+        add_column_if_missing(&conn, "runs", "tags", "TEXT NOT NULL DEFAULT ''")?;
+        add_column_if_missing(&conn, "runs", "logs_dir", "TEXT")?;
+        add_column_if_missing(&conn, "runs", "partial", "INTEGER NOT NULL DEFAULT 0")?;
+        Ok(History { conn })
+    }
+
+    /// Stores a finished run and returns its id.
+    pub fn record_run(&mut self, run: &RunResults, config_snapshot: &str) -> rusqlite::Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs
+                 (timestamp, total_score, num_cases, config, comment, tags, logs_dir, partial)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run.timestamp,
+                run.total_score,
+                run.num_cases as i64,
+                config_snapshot,
+                run.comment,
+                run.tags.join(","),
+                run.logs_dir,
+                run.partial()
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO cases (run_id, seed, input_file, score, elapsed_ms, verdict)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for result in &run.results {
+                stmt.execute(params![
+                    run_id,
+                    result.seed as i64,
+                    result.input_file,
+                    result.score,
+                    result.elapsed_ms as i64,
+                    result.verdict.as_str()
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// All recorded runs, oldest first.
+    pub fn run_summaries(&self) -> rusqlite::Result<Vec<RunSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, total_score, num_cases, comment, tags, logs_dir, partial
+             FROM runs ORDER BY id",
+        )?;
+        let runs = stmt
+            .query_map([], |row| {
+                Ok(RunSummary {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    total_score: row.get(2)?,
+                    num_cases: row.get::<_, i64>(3)? as usize,
+                    comment: row.get(4)?,
+                    tags: split_tags(&row.get::<_, String>(5)?),
+                    logs_dir: row.get(6)?,
+                    partial: row.get(7)?,
+                })
+            })?
+            .collect();
+        runs
+    }
+
+    /// Each run's headline score combined as `aggregate` and `weights` say,
+    /// e.g. as configured now, so runs recorded under other settings rank
+    /// alike. Empty for a plain sum, which `total_score` holds exactly.
+    pub fn headline_scores(
+        &self,
+        aggregate: Aggregate,
+        weights: &BTreeMap<usize, f64>,
+    ) -> rusqlite::Result<HashMap<i64, f64>> {
+        if aggregate.combine(&[], weights).is_none() {
+            return Ok(HashMap::new());
+        }
+        let mut stmt = self
+            .conn
+            .prepare("SELECT run_id, seed, input_file, score FROM cases")?;
+        let mut cases: HashMap<i64, Vec<Result>> = HashMap::new();
+        let rows = stmt.query_map([], |row| {
+            let input_file: String = row.get(2)?;
+            Ok((
+                row.get::<_, i64>(0)?,
+                Result {
+                    seed: row.get::<_, i64>(1)? as usize,
+                    score: row.get(3)?,
+                    ..Result::empty(&input_file, "")
+                },
+            ))
+        })?;
+        for row in rows {
+            let (run_id, result) = row?;
+            cases.entry(run_id).or_default().push(result);
+        }
+        Ok(cases
+            .into_iter()
+            .map(|(run_id, results)| (run_id, aggregate.apply(&results, weights)))
+            .collect())
+    }
+
+    /// Loads the most recent run over all the seeds, the default baseline;
+    /// a `--quick` or `--seeds` run's total does not compare with a full one.
+    pub fn last_full_run(&self) -> rusqlite::Result<Option<RunResults>> {
+        let run_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM runs WHERE partial = 0 ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match run_id {
+            Some(id) => self.load_run(id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the run recorded before the most recent one, if any.
+    pub fn run_before_last(&self) -> rusqlite::Result<Option<RunResults>> {
+        self.nth_last_run(1)
+    }
+
+    /// How long each seed took in the most recent run that had it.
+    pub fn seed_times(&self) -> rusqlite::Result<HashMap<usize, u64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cases.seed, cases.elapsed_ms FROM cases
+             JOIN (SELECT seed, MAX(run_id) AS run_id FROM cases GROUP BY seed) AS latest
+             ON cases.seed = latest.seed AND cases.run_id = latest.run_id",
+        )?;
+        let times = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect();
+        times
+    }
+
+    fn nth_last_run(&self, n: i64) -> rusqlite::Result<Option<RunResults>> {
+        let run_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM runs ORDER BY id DESC LIMIT 1 OFFSET ?1",
+                [n],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match run_id {
+            Some(id) => self.load_run(id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn load_run(&self, run_id: i64) -> rusqlite::Result<RunResults> {
+        let (timestamp, total_score, comment, tags): (String, i64, Option<String>, String) =
+            self.conn.query_row(
+                "SELECT timestamp, total_score, comment, tags FROM runs WHERE id = ?1",
+                params![run_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )?;
+        let mut stmt = self.conn.prepare(
+            "SELECT seed, input_file, score, elapsed_ms, verdict FROM cases
+             WHERE run_id = ?1 ORDER BY seed",
+        )?;
+        let results = stmt
+            .query_map(params![run_id], |row| {
+                let input_file: String = row.get(1)?;
+                let score = row.get::<_, i64>(2)?;
+                Ok(Result {
+                    seed: row.get::<_, i64>(0)? as usize,
+                    score,
+                    score_string: format_score(score),
+                    elapsed_ms: row.get::<_, i64>(3)? as u64,
+                    verdict: Verdict::parse(&row.get::<_, String>(4)?).unwrap_or_default(),
+                    ..Result::empty(&input_file, "")
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut run = RunResults::new(timestamp, total_score, results);
+        run.run_id = Some(run_id);
+        run.comment = comment;
+        run.tags = split_tags(&tags);
+        Ok(run)
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Databases created by older versions lack columns added since; add them in place.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
+
+/// When a run finished, from its recorded local timestamp.
+pub fn finished_at(timestamp: &str) -> Option<SystemTime> {
+    let naive = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+pub fn history_path(config: &Config) -> &str {
+    config
+        .paths
+        .history_db
+        .as_deref()
+        .unwrap_or(".ahc_history.db")
+}
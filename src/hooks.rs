@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::progress::log;
+use crate::runner::shell_command;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Shell commands run before any seed; the run is skipped if one fails
+    #[serde(default)]
+    pub pre_run: Vec<String>,
+    /// Shell commands run after the report has been written
+    #[serde(default)]
+    pub post_run: Vec<String>,
+}
+
+/// Runs `commands` in order through the shell with `envs` set, stopping at
+/// the first one that fails.
+pub fn run_hooks(stage: &str, commands: &[String], envs: &[(&str, String)]) -> io::Result<()> {
+    for command in commands {
+        log!("[{}] {}", stage, command);
+        let status = shell_command(command)
+            .envs(envs.iter().map(|(k, v)| (*k, v)))
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "{} hook `{}` failed with {}",
+                stage, command, status
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1089 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::json;
+use tera::{Context, Tera};
+
+use crate::charts::{histogram_svg, scatter_svg, trend_svg, Scale};
+use crate::colors::{gradient, Rgb};
+use crate::commands::compare::Comparison;
+use crate::commands::ensemble::Ensemble;
+use crate::config::Config;
+use crate::history::{History, RunSummary};
+use crate::progress::log;
+use crate::results::{Result, RunResults, Verdict};
+use crate::runner::extract_number;
+use crate::scoring::{load_weights, potential_score, Objective};
+use crate::standings::{load_rivals, standings, YOU};
+use crate::stats::{extrapolate, group_by_feature, summarize, Bucket, Summary};
+
+pub fn generate_html(run: &RunResults, baseline: Option<&RunResults>, config: &Config) {
+    write_report(run, baseline, None, config);
+}
+
+/// Writes the report for a run that is still going, with a banner showing
+/// how many of `total_cases` are done. The page reloads itself periodically.
+pub fn generate_progress_html(
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+    total_cases: usize,
+    config: &Config,
+) {
+    write_report(run, baseline, Some(total_cases), config);
+}
+
+/// Built-in report layout, overridden by `report.template`.
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/report.html");
+
+fn write_report(
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+    total_cases: Option<usize>,
+    config: &Config,
+) {
+    let output_path = &config.paths.html_output;
+    let template = match config.report.as_ref().and_then(|r| r.template.as_deref()) {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(template) => template,
+            Err(e) => {
+                log!("Error reading report template {}: {}", path, e);
+                return;
+            }
+        },
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let context = report_context(run, baseline, total_cases, config);
+    let html = match Tera::one_off(&template, &context, true) {
+        Ok(html) => html,
+        Err(e) => {
+            // Tera keeps the useful part (line, unknown variable) in the sources
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message.push_str(&format!(": {}", cause));
+                source = cause.source();
+            }
+            log!("Error rendering report template: {}", message);
+            return;
+        }
+    };
+
+    if let Err(e) = fs::write(output_path, html) {
+        log!("Error writing HTML file: {}", e);
+    }
+}
+
+/// Everything the report template can use. Summary figures come ready to
+/// print; `rows` holds each result alongside its rendered `<td>` cells, in
+/// the order of `columns` (plus the Visualizer cell when `show_visualizer`).
+fn report_context(
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+    total_cases: Option<usize>,
+    config: &Config,
+) -> Context {
+    let results = &run.results;
+    let feature_names: Vec<&str> = config
+        .features
+        .as_ref()
+        .map(|f| f.columns().collect())
+        .unwrap_or_default();
+    let mut context = Context::new();
+
+    // Clicking a numeric column sorts best-first according to the objective
+    let better_first = match run.objective {
+        Objective::Max => "desc",
+        Objective::Min => "asc",
+    };
+    context.insert("better_first", better_first);
+    let preview = config.report.as_ref().is_some_and(|r| r.preview);
+    context.insert("preview", &preview);
+    context.insert(
+        "theme",
+        &config.report.as_ref().map(|r| r.theme).unwrap_or_default(),
+    );
+    context.insert("timestamp", &run.timestamp);
+    context.insert("run_id", &run.run_id);
+    context.insert("comment", &run.comment);
+    context.insert(
+        "logs",
+        &run.logs_dir.as_ref().map(|dir| {
+            let report = Path::new(&config.paths.html_output);
+            json!({
+                "dir": dir,
+                "run_log": relative_link(report, &Path::new(dir).join("run.log")),
+            })
+        }),
+    );
+    context.insert("tags", &run.tags);
+    context.insert("params", &run.params);
+    context.insert("git", &run.git.as_ref().map(|git| git.describe()));
+    context.insert(
+        "progress",
+        &total_cases.map(|total| json!({ "done": results.len(), "total": total })),
+    );
+    context.insert("interrupted", &run.interrupted);
+    context.insert("score_label", &run.score_label());
+    context.insert("total_score", &run.headline_score());
+    context.insert(
+        "baseline",
+        &baseline.map(|baseline| {
+            let (delta, sign) = run.headline_delta(baseline);
+            json!({
+                "class": delta_class(sign, run.objective),
+                "delta": delta,
+                "total_score": run.headline_score_of(baseline),
+                "timestamp": baseline.timestamp,
+            })
+        }),
+    );
+    context.insert(
+        "sampled",
+        &run.sampled_from
+            .zip(run.extrapolated_total())
+            .map(|(available, extrapolated)| {
+                json!({
+                    "available": available,
+                    "extrapolated": extrapolated,
+                    "interval": run.extrapolated_interval(),
+                })
+            }),
+    );
+    context.insert("total_relative_score", &run.total_relative_score);
+    context.insert(
+        "potential_total",
+        &run.total_relative_score.map(|_| {
+            results
+                .iter()
+                .filter_map(|r| potential_score(r, run.objective))
+                .fold(0i64, |sum, p| sum.saturating_add(p))
+        }),
+    );
+    let rivals = config
+        .scoring
+        .as_ref()
+        .and_then(|s| s.rivals.as_ref())
+        .and_then(|path| match load_rivals(path) {
+            Ok(rivals) => Some(rivals),
+            Err(e) => {
+                log!("Error loading rivals from {}: {}", path, e);
+                None
+            }
+        });
+    context.insert(
+        "standings",
+        &rivals.map(|rivals| {
+            let standings = standings(run, &rivals);
+            let rank = standings.iter().find(|s| s.name == YOU).map(|s| s.rank);
+            json!({
+                "rank": rank,
+                "relative": standings.iter().any(|s| s.relative.is_some()),
+                "entries": standings,
+            })
+        }),
+    );
+    context.insert(
+        "system_test",
+        &config
+            .scoring
+            .as_ref()
+            .and_then(|s| s.system_test_seeds)
+            .and_then(|seeds| extrapolate(results, seeds))
+            .map(|e| {
+                let seeds = e.seeds as f64;
+                json!({
+                    "seeds": e.seeds,
+                    "mean": format!("{:.1}", e.mean),
+                    "half_width": format!("{:.1}", e.half_width),
+                    "total": format!("{:.0}", e.mean * seeds),
+                    "low": format!("{:.0}", (e.mean - e.half_width) * seeds),
+                    "high": format!("{:.0}", (e.mean + e.half_width) * seeds),
+                    "dominant_seeds": e.dominant_seeds,
+                    "dominant_percent": format!("{:.0}", e.dominant_share * 100.0),
+                })
+            }),
+    );
+    context.insert("best", &run.objective.best(results.iter()));
+    context.insert("worst", &run.objective.worst(results.iter()));
+    let verdicts: Vec<_> = Verdict::ALL
+        .iter()
+        .map(|&v| {
+            let count = results.iter().filter(|r| r.verdict == v).count();
+            json!({ "verdict": v.as_str(), "count": count })
+        })
+        .collect();
+    context.insert("verdicts", &verdicts);
+    context.insert(
+        "time",
+        &results.iter().max_by_key(|r| r.elapsed_ms).map(|slowest| {
+            let mean_ms =
+                results.iter().map(|r| r.elapsed_ms).sum::<u64>() as f64 / results.len() as f64;
+            json!({
+                "max_ms": slowest.elapsed_ms,
+                "max_seed": slowest.seed,
+                "mean_ms": format!("{:.0}", mean_ms),
+            })
+        }),
+    );
+
+    let scores: Vec<f64> = results.iter().map(|r| r.score as f64).collect();
+    let times: Vec<f64> = results.iter().map(|r| r.elapsed_ms as f64).collect();
+    // Every chart is drawn on both scales; the report shows one at a time
+    let mut charts = Vec::new();
+    let mut push_chart = |title: String, draw: &dyn Fn(Scale) -> Option<String>| {
+        if let Some(svg) = draw(Scale::Linear) {
+            charts.push(json!({ "title": title, "svg": svg, "log_svg": draw(Scale::Log) }));
+        }
+    };
+    push_chart("Score Distribution".to_string(), &|scale| {
+        histogram_svg(&scores, scale)
+    });
+    let by_seed: Vec<(f64, f64)> = results
+        .iter()
+        .map(|r| (r.seed as f64, r.score as f64))
+        .collect();
+    push_chart("Score by Seed".to_string(), &|scale| {
+        scatter_svg(&by_seed, "seed", scale)
+    });
+    for name in &feature_names {
+        let by_feature: Vec<(f64, f64)> = results
+            .iter()
+            .filter_map(|r| Some((*r.features.get(*name)?, r.score as f64)))
+            .collect();
+        push_chart(format!("Score by {}", name), &|scale| {
+            scatter_svg(&by_feature, &tera::escape_html(name), scale)
+        });
+    }
+    context.insert("charts", &charts);
+    context.insert(
+        "log_scale",
+        &config.report.as_ref().is_some_and(|r| r.log_scale),
+    );
+
+    let summaries: Vec<(&str, Summary)> = [("Score", &scores), ("Time (ms)", &times)]
+        .into_iter()
+        .filter_map(|(label, values)| Some((label, summarize(values)?)))
+        .collect();
+    let mut statistics = String::new();
+    if !summaries.is_empty() {
+        statistics.push_str(&summary_table(&summaries));
+    }
+    if let Some(features) = config.features.as_ref() {
+        if let Some(ref feature) = features.group_by {
+            let buckets = group_by_feature(results, feature, features.bucket_count);
+            statistics.push_str(&bucket_table(feature, &buckets));
+        }
+    }
+    context.insert("statistics", statistics.trim_end());
+
+    let mut columns = vec![Column::new("Input File", false, "file")];
+    for name in &feature_names {
+        columns.push(Column::new(name, true, &format!("feature_{}", name)));
+    }
+    columns.push(Column::new("Verdict", false, "verdict"));
+    columns.push(Column::new("Score", true, "score"));
+    columns.push(Column {
+        class: "log-only",
+        ..Column::new("log₁₀", true, "log_score")
+    });
+    if run.total_relative_score.is_some() {
+        columns.push(Column::new("Relative", true, "relative"));
+        columns.push(Column::new("Potential", true, "potential"));
+    }
+    columns.push(Column::new("Time (ms)", true, "time"));
+    let show_memory = results.iter().any(|r| r.peak_rss_kb.is_some());
+    if show_memory {
+        columns.push(Column::new("Memory (MB)", true, "memory"));
+    }
+    for metric in &config.tester.metrics {
+        // Apart from the built-in keys, as a metric may be called "score"
+        columns.push(Column::new(
+            &metric.name,
+            true,
+            &format!("metric:{}", metric.name),
+        ));
+    }
+    if baseline.is_some() {
+        columns.push(Column::new("Δ", true, "delta"));
+    }
+    context.insert("columns", &columns);
+    let show_files = results.iter().any(|r| !r.input_file.is_empty());
+    context.insert("show_files", &show_files);
+    // Runs without visualization get no Visualizer column
+    let show_visualizer = results.iter().any(|r| !r.visualizer.is_empty());
+    context.insert("show_visualizer", &show_visualizer);
+
+    let baseline_scores: Option<HashMap<usize, i64>> = baseline.map(|b| {
+        b.results
+            .iter()
+            .map(|r| (extract_number(&r.input_file), r.score))
+            .collect()
+    });
+    let heatmap = config
+        .report
+        .as_ref()
+        .filter(|r| r.heatmap)
+        .and_then(|r| Heatmap::new(results, run.objective, &r.heatmap_colors));
+    let rows: Vec<_> = results
+        .iter()
+        .map(|result| {
+            let mut cells = vec![format!("<td>{}</td>", result.input_file)];
+            for name in &feature_names {
+                cells.push(format!(
+                    "<td>{}</td>",
+                    result
+                        .features
+                        .get(*name)
+                        .map_or_else(|| "-".to_string(), |v| v.to_string())
+                ));
+            }
+            let attempts = if result.attempts > 1 {
+                format!(" ({} attempts)", result.attempts)
+            } else {
+                String::new()
+            };
+            cells.push(format!(
+                r#"<td class="verdict-{0}">{0}{1}</td>"#,
+                result.verdict.as_str(),
+                attempts
+            ));
+            cells.push(score_cell(result, heatmap.as_ref()));
+            cells.push(format!(
+                r#"<td class="log-only">{}</td>"#,
+                if result.score > 0 {
+                    format!("{:.3}", (result.score as f64).log10())
+                } else {
+                    "-".to_string()
+                }
+            ));
+            if run.total_relative_score.is_some() {
+                cells.push(format!(
+                    "<td>{}</td>",
+                    result
+                        .relative_score
+                        .map_or_else(|| "-".to_string(), |r| r.to_string())
+                ));
+                cells.push(format!(
+                    "<td>{}</td>",
+                    potential_score(result, run.objective)
+                        .map_or_else(|| "-".to_string(), |p| p.to_string())
+                ));
+            }
+            cells.push(format!("<td>{}</td>", result.elapsed_ms));
+            if show_memory {
+                cells.push(memory_cell(result, config.tester.memory_warning_mb));
+            }
+            for metric in &config.tester.metrics {
+                cells.push(format!(
+                    "<td>{}</td>",
+                    result
+                        .metrics
+                        .get(&metric.name)
+                        .map_or_else(|| "-".to_string(), |v| v.to_string())
+                ));
+            }
+            if let Some(ref scores) = baseline_scores {
+                cells.push(delta_cell(result, scores, run.objective));
+            }
+            if show_files {
+                cells.push(files_cell(result, &config.paths.html_output));
+            }
+            if show_visualizer {
+                cells.push(visualizer_cell(result, preview));
+            }
+            json!({ "result": result, "cells": cells })
+        })
+        .collect();
+    context.insert("rows", &rows);
+    context
+}
+
+/// Score range of the AC results, mapped onto a color gradient. Failed seeds
+/// are left out so their zero score does not stretch the scale.
+struct Heatmap<'a> {
+    worst: i64,
+    best: i64,
+    objective: Objective,
+    colors: &'a [Rgb],
+}
+
+impl<'a> Heatmap<'a> {
+    fn new(results: &[Result], objective: Objective, colors: &'a [Rgb]) -> Option<Self> {
+        let accepted = || results.iter().filter(|r| r.verdict == Verdict::Ac);
+        Some(Heatmap {
+            worst: objective.gain(objective.worst(accepted())?.score),
+            best: objective.gain(objective.best(accepted())?.score),
+            objective,
+            colors,
+        })
+    }
+
+    fn color(&self, result: &Result) -> Option<Rgb> {
+        if result.verdict != Verdict::Ac {
+            return None;
+        }
+        let t = if self.best == self.worst {
+            1.0
+        } else {
+            (self.objective.gain(result.score) - self.worst) as f64
+                / (self.best - self.worst) as f64
+        };
+        gradient(self.colors, t)
+    }
+}
+
+fn score_cell(result: &Result, heatmap: Option<&Heatmap>) -> String {
+    match heatmap.and_then(|h| h.color(result)) {
+        Some(color) => format!(
+            r#"<td class="heat" style="background-color: {}">{}</td>"#,
+            color, result.score_string
+        ),
+        None => format!("<td>{}</td>", result.score_string),
+    }
+}
+
+/// Links to the case's input, solver output and logs, where they exist.
+fn files_cell(result: &Result, report: &str) -> String {
+    let links: Vec<String> = [
+        ("input", &result.input_file),
+        ("output", &result.output_file),
+        ("stderr", &result.stderr_file),
+        ("solver stderr", &result.solver_stderr_file),
+        ("command", &result.case_file),
+    ]
+    .into_iter()
+    .filter(|(_, path)| !path.is_empty() && Path::new(path).is_file())
+    .map(|(label, path)| {
+        format!(
+            r#"<a href="{}" target="_blank">{}</a>"#,
+            relative_link(Path::new(report), Path::new(path)),
+            label
+        )
+    })
+    .collect();
+    format!("<td>{}</td>", links.join(" / "))
+}
+
+/// Path from the directory of `report` to `target`, so links keep working
+/// wherever the report is opened from.
+fn relative_link(report: &Path, target: &Path) -> String {
+    let absolute = |p: &Path| std::path::absolute(p).unwrap_or_else(|_| p.to_path_buf());
+    let from = absolute(report.parent().unwrap_or(Path::new(".")));
+    let to = absolute(target);
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    parts.join("/")
+}
+
+fn visualizer_cell(result: &Result, preview: bool) -> String {
+    let onclick = if preview {
+        r#" class="vis-link" onclick="return openPreview(this)""#
+    } else {
+        ""
+    };
+    let label = if result.thumbnail.is_empty() {
+        "View".to_string()
+    } else {
+        format!(
+            "<img class=\"thumbnail\" src=\"{}\" alt=\"seed {}\" loading=\"lazy\">",
+            result.thumbnail, result.seed
+        )
+    };
+    format!(
+        "<td><a href=\"{}\" target=\"_blank\"{}>{}</a></td>",
+        result.visualizer, onclick, label
+    )
+}
+
+fn summary_table(summaries: &[(&str, Summary)]) -> String {
+    let mut html = String::from(
+        r#"    <h2>Summary</h2>
+    <table class="summary">
+        <thead>
+            <tr>
+                <th></th>
+                <th>Mean</th>
+                <th>Median</th>
+                <th>Std Dev</th>
+                <th>Min</th>
+                <th>Max</th>
+                <th>P5</th>
+                <th>P95</th>
+            </tr>
+        </thead>
+        <tbody>
+"#,
+    );
+    for (label, s) in summaries {
+        html.push_str(&format!(
+            r#"            <tr>
+                <th>{}</th>
+                <td>{:.1}</td>
+                <td>{:.1}</td>
+                <td>{:.1}</td>
+                <td>{:.0}</td>
+                <td>{:.0}</td>
+                <td>{:.1}</td>
+                <td>{:.1}</td>
+            </tr>
+"#,
+            label, s.mean, s.median, s.stddev, s.min, s.max, s.p5, s.p95
+        ));
+    }
+    html.push_str(
+        r#"        </tbody>
+    </table>
+"#,
+    );
+    html
+}
+
+fn bucket_table(feature: &str, buckets: &[Bucket]) -> String {
+    let mut html = format!(
+        "    <h2>Score Groups by {}</h2>\n",
+        tera::escape_html(feature)
+    );
+    html.push_str(
+        r#"    <table class="summary">
+        <thead>
+            <tr>
+                <th>Group</th>
+                <th>Count</th>
+                <th>Mean</th>
+                <th>Min</th>
+                <th>Max</th>
+            </tr>
+        </thead>
+        <tbody>
+"#,
+    );
+    for bucket in buckets {
+        html.push_str(&format!(
+            r#"            <tr>
+                <td>{}</td>
+                <td>{}</td>
+                <td>{:.1}</td>
+                <td>{}</td>
+                <td>{}</td>
+            </tr>
+"#,
+            bucket.label, bucket.count, bucket.mean, bucket.min, bucket.max
+        ));
+    }
+    html.push_str(
+        r#"        </tbody>
+    </table>
+"#,
+    );
+    html
+}
+
+/// A sortable column of the results table; its position is the index
+/// `sortTable` sorts by. The label is text, escaped like any other, since
+/// features and metrics are named by the user.
+#[derive(Serialize)]
+struct Column {
+    label: String,
+    numeric: bool,
+    key: String,
+    /// CSS class of the header, e.g. `log-only` for columns shown only on
+    /// the log scale
+    class: &'static str,
+}
+
+impl Column {
+    fn new(label: &str, numeric: bool, key: &str) -> Self {
+        Column {
+            label: label.to_string(),
+            numeric,
+            key: key.to_string(),
+            class: "",
+        }
+    }
+}
+
+fn memory_cell(result: &Result, warning_mb: Option<u64>) -> String {
+    match result.peak_rss_kb {
+        Some(rss_kb) => {
+            let over = warning_mb.is_some_and(|limit| rss_kb > limit * 1024);
+            format!(
+                "<td{}>{:.1}</td>",
+                if over { r#" class="mem-warn""# } else { "" },
+                rss_kb as f64 / 1024.0
+            )
+        }
+        None => "<td>-</td>".to_string(),
+    }
+}
+
+fn delta_cell(
+    result: &Result,
+    baseline_scores: &HashMap<usize, i64>,
+    objective: Objective,
+) -> String {
+    match baseline_scores.get(&extract_number(&result.input_file)) {
+        Some(&base_score) => {
+            let delta = result.score.saturating_sub(base_score);
+            format!(
+                "<td class=\"{}\">{:+}</td>",
+                delta_class(delta, objective),
+                delta
+            )
+        }
+        None => "<td>-</td>".to_string(),
+    }
+}
+
+/// CSS class for a score difference, green when it is an improvement.
+fn delta_class(delta: i64, objective: Objective) -> &'static str {
+    match objective.gain(delta).signum() {
+        1 => "delta-pos",
+        -1 => "delta-neg",
+        _ => "",
+    }
+}
+
+/// Paragraphs listing the seeds that gained and lost the most.
+fn swings_html(comparison: &Comparison) -> String {
+    let mut html = String::new();
+    for (label, swings, class) in [
+        ("Largest gains", &comparison.largest_wins, "delta-pos"),
+        ("Largest losses", &comparison.largest_losses, "delta-neg"),
+    ] {
+        if swings.is_empty() {
+            continue;
+        }
+        let list: Vec<String> = swings
+            .iter()
+            .map(|&i| comparison.swing(&comparison.cases[i]))
+            .collect();
+        html.push_str(&format!(
+            "    <p>{}: <span class=\"{}\">{}</span></p>\n",
+            label,
+            class,
+            list.join(", ")
+        ));
+    }
+    html
+}
+
+pub fn generate_compare_html(comparison: &Comparison, output_path: &str) {
+    let mut html = String::from(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Score Comparison</title>
+    <style>
+        table {
+            width: 100%;
+            border-collapse: collapse;
+        }
+        th, td {
+            border: 1px solid #ddd;
+            padding: 8px;
+        }
+        th {
+            background-color: #f2f2f2;
+            text-align: left;
+        }
+        tr:hover {
+            background-color: #f5f5f5;
+        }
+        .delta-pos {
+            color: #1a7f37;
+        }
+        .delta-neg {
+            color: #cf222e;
+        }
+    </style>
+</head>
+<body>
+    <h1>Score Comparison</h1>
+"#,
+    );
+
+    html.push_str(&format!(
+        r#"    <p>Base: {}</p>
+    <p>Target: {}</p>
+    <p>Win/Loss/Tie: {}</p>
+{}    <p>Summed difference: <span class="{}">{:+}</span></p>
+    <p>{}</p>
+    <table>
+        <thead>
+            <tr>
+                <th>Seed</th>
+                <th>Base</th>
+                <th>Target</th>
+                <th>&Delta;</th>
+"#,
+        comparison.base_name,
+        comparison.target_name,
+        comparison.win_loss_tie(),
+        swings_html(comparison),
+        delta_class(comparison.delta_sum, comparison.objective),
+        comparison.delta_sum,
+        comparison.significance()
+    ));
+    for extra in &comparison.extras {
+        html.push_str(&format!("                <th>{}</th>\n", extra.name));
+    }
+    html.push_str(
+        r#"            </tr>
+        </thead>
+        <tbody>
+"#,
+    );
+
+    for case in &comparison.cases {
+        html.push_str(&format!(
+            r#"            <tr>
+                <td>{}</td>
+                <td>{}</td>
+                <td>{}</td>
+                <td class="{}">{:+}</td>
+"#,
+            case.seed,
+            case.base,
+            case.target,
+            delta_class(case.delta(), comparison.objective),
+            case.delta()
+        ));
+        for extra in &comparison.extras {
+            html.push_str(&format!(
+                "                <td>{}</td>\n",
+                extra.cell(case.seed)
+            ));
+        }
+        html.push_str("            </tr>\n");
+    }
+
+    html.push_str(
+        r#"        </tbody>
+    </table>
+</body>
+</html>
+"#,
+    );
+
+    if let Err(e) = fs::write(output_path, html) {
+        log!("Error writing HTML file: {}", e);
+    }
+}
+
+/// Page with the best answer per seed and the run each came from.
+pub fn generate_ensemble_html(ensemble: &Ensemble, output_path: &str) {
+    let mut html = String::from(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Best-per-seed Ensemble</title>
+    <style>
+        table {
+            width: 100%;
+            border-collapse: collapse;
+            margin-bottom: 20px;
+        }
+        th, td {
+            border: 1px solid #ddd;
+            padding: 8px;
+        }
+        th {
+            background-color: #f2f2f2;
+            text-align: left;
+        }
+        tr:hover {
+            background-color: #f5f5f5;
+        }
+    </style>
+</head>
+<body>
+    <h1>Best-per-seed Ensemble</h1>
+"#,
+    );
+
+    html.push_str(&format!(
+        "    <p>Ensemble total: {}</p>\n",
+        ensemble.total_score
+    ));
+    if let Some(best) = ensemble.best_single() {
+        html.push_str(&format!(
+            "    <p>Best single run: {} ({}); the ensemble gains {:+}</p>\n",
+            best.total_score,
+            best.name,
+            ensemble.total_score.saturating_sub(best.total_score)
+        ));
+    }
+
+    html.push_str(
+        r#"    <h2>Runs</h2>
+    <table>
+        <thead>
+            <tr>
+                <th>Run</th>
+                <th>Total Score</th>
+                <th>Seeds Won</th>
+            </tr>
+        </thead>
+        <tbody>
+"#,
+    );
+    for source in &ensemble.sources {
+        html.push_str(&format!(
+            r#"            <tr>
+                <td>{}</td>
+                <td>{}</td>
+                <td>{}</td>
+            </tr>
+"#,
+            source.name, source.total_score, source.seeds_won
+        ));
+    }
+
+    html.push_str(
+        r#"        </tbody>
+    </table>
+    <h2>Seeds</h2>
+    <table>
+        <thead>
+            <tr>
+                <th>Seed</th>
+                <th>Best Score</th>
+                <th>Run</th>
+            </tr>
+        </thead>
+        <tbody>
+"#,
+    );
+    for seed in &ensemble.seeds {
+        html.push_str(&format!(
+            r#"            <tr>
+                <td>{}</td>
+                <td>{}</td>
+                <td>{}</td>
+            </tr>
+"#,
+            seed.seed, seed.score, ensemble.sources[seed.source].name
+        ));
+    }
+
+    html.push_str(
+        r#"        </tbody>
+    </table>
+</body>
+</html>
+"#,
+    );
+
+    if let Err(e) = fs::write(output_path, html) {
+        log!("Error writing HTML file: {}", e);
+    }
+}
+
+/// Where the report of a recorded run is kept, next to `html_output` so its
+/// relative links keep working.
+pub fn archived_report_path(config: &Config, run_id: i64) -> PathBuf {
+    Path::new(&config.paths.html_output).with_file_name(format!("run_{}.html", run_id))
+}
+
+pub fn trend_path(config: &Config) -> PathBuf {
+    match config.paths.trend_output {
+        Some(ref path) => PathBuf::from(path),
+        None => Path::new(&config.paths.html_output).with_file_name("trend.html"),
+    }
+}
+
+/// Page with the headline score of every recorded run, each linking to that
+/// run's report. Scores are combined as `scoring` says now, so the trend
+/// ranks runs the way the report does; runs over only some of the seeds
+/// are listed but left off the chart, where their totals would look like
+/// collapses.
+pub fn generate_trend_html(history: &History, config: &Config) -> rusqlite::Result<()> {
+    let output_path = trend_path(config);
+    let aggregate = config
+        .scoring
+        .as_ref()
+        .map(|s| s.aggregate)
+        .unwrap_or_default();
+    let weights = load_weights(config);
+    let runs = history.run_summaries()?;
+    let headline_scores = history.headline_scores(aggregate, &weights)?;
+    let score = |run: &RunSummary| {
+        headline_scores
+            .get(&run.id)
+            .copied()
+            .unwrap_or(run.total_score as f64)
+    };
+    let score_string = |run: &RunSummary| format!("{:.*}", aggregate.precision(), score(run));
+    // Empty for runs whose report was pruned
+    let link = |run: &RunSummary| {
+        let archived = archived_report_path(config, run.id);
+        if !archived.is_file() {
+            return String::new();
+        }
+        archived
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut html = String::from(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Run History</title>
+    <style>
+        table {
+            width: 100%;
+            border-collapse: collapse;
+        }
+        th, td {
+            border: 1px solid #ddd;
+            padding: 8px;
+        }
+        th {
+            background-color: #f2f2f2;
+            text-align: left;
+        }
+        tr:hover {
+            background-color: #f5f5f5;
+        }
+        .tag {
+            border: 1px solid #ddd;
+            border-radius: 4px;
+            padding: 0 4px;
+            cursor: pointer;
+        }
+    </style>
+    <script>
+        // Shows the runs carrying every tag, or mentioning every word, of the filter
+        function filterRuns() {
+            const words = document.getElementById('filterRuns').value.trim().toLowerCase()
+                .split(/\s+/).filter(word => word);
+            document.querySelectorAll('tbody tr').forEach(row => {
+                const tags = row.dataset.tags.split(',');
+                const comment = row.querySelector('.comment').textContent.toLowerCase();
+                const shown = words.every(word => tags.includes(word) || comment.includes(word));
+                row.style.display = shown ? '' : 'none';
+            });
+        }
+
+        function filterTag(tag) {
+            document.getElementById('filterRuns').value = tag;
+            filterRuns();
+        }
+    </script>
+</head>
+<body>
+    <h1>Run History</h1>
+"#,
+    );
+
+    let points: Vec<(String, f64, String)> = runs
+        .iter()
+        .filter(|run| !run.partial)
+        .map(|run| {
+            (
+                match run.comment {
+                    Some(ref comment) => format!(
+                        "#{} {}: {} ({})",
+                        run.id,
+                        run.timestamp,
+                        score_string(run),
+                        tera::escape_html(comment)
+                    ),
+                    None => format!("#{} {}: {}", run.id, run.timestamp, score_string(run)),
+                },
+                score(run),
+                link(run),
+            )
+        })
+        .collect();
+    if let Some(svg) = trend_svg(&points) {
+        html.push_str("    ");
+        html.push_str(&svg);
+    }
+
+    html.push_str(&format!(
+        r#"    <p>
+        <input id="filterRuns" type="search" placeholder="Tags or comment words" oninput="filterRuns()">
+    </p>
+    <table>
+        <thead>
+            <tr>
+                <th>Run</th>
+                <th>Timestamp (JST)</th>
+                <th>Cases</th>
+                <th>{} Score</th>
+                <th>Comment</th>
+                <th>Tags</th>
+            </tr>
+        </thead>
+        <tbody>
+"#,
+        aggregate.score_label(&weights)
+    ));
+    for run in runs.iter().rev() {
+        let tags: Vec<String> = run
+            .tags
+            .iter()
+            .map(|tag| {
+                format!(
+                    r#"<span class="tag" onclick="filterTag(this.textContent)">{}</span>"#,
+                    tera::escape_html(tag)
+                )
+            })
+            .collect();
+        html.push_str(&format!(
+            r#"            <tr data-tags="{}">
+                <td>{}</td>
+                <td>{}</td>
+                <td>{}</td>
+                <td>{}</td>
+                <td class="comment">{}</td>
+                <td>{}</td>
+            </tr>
+"#,
+            tera::escape_html(&run.tags.join(",").to_lowercase()),
+            match link(run).as_str() {
+                "" => format!("#{}", run.id),
+                href => format!(r#"<a href="{}">#{}</a>"#, href, run.id),
+            },
+            run.timestamp,
+            if run.partial {
+                format!("{} (partial)", run.num_cases)
+            } else {
+                run.num_cases.to_string()
+            },
+            score_string(run),
+            tera::escape_html(run.comment.as_deref().unwrap_or("")),
+            tags.join(" ")
+        ));
+    }
+    html.push_str(
+        r#"        </tbody>
+    </table>
+</body>
+</html>
+"#,
+    );
+
+    if let Err(e) = fs::write(&output_path, html) {
+        log!("Error writing trend page: {}", e);
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::progress::log;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static TESTERS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Installs the Ctrl-C handler. During a run the first Ctrl-C stops new
+/// seeds from starting and lets the ones in flight finish so a partial
+/// report can be written; a second one, or one outside a run, kills the
+/// testers and exits.
+pub fn install() {
+    let result = ctrlc::set_handler(|| {
+        if RUNNING.load(Ordering::SeqCst) && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            log!("\nInterrupted: waiting for running seeds to finish; press Ctrl-C again to abort");
+        } else {
+            abort();
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("Error installing Ctrl-C handler: {}", e);
+    }
+}
+
+/// Stops the current run from starting new seeds, without the Ctrl-C
+/// escalation.
+pub fn stop_run() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Asks the current run to stop, as the first Ctrl-C does.
+pub fn request() {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        abort();
+    }
+}
+
+/// Kills every running tester and exits.
+pub fn abort() -> ! {
+    for &pid in TESTERS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        kill_tester(pid);
+    }
+    std::process::exit(130);
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Marks a run as in progress until the guard is dropped.
+pub struct RunGuard;
+
+impl RunGuard {
+    pub fn new() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        RUNNING.store(true, Ordering::SeqCst);
+        RunGuard
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Records a running tester so an abort can kill it.
+pub fn track_tester(pid: u32) {
+    TESTERS.lock().unwrap_or_else(|e| e.into_inner()).push(pid);
+}
+
+pub fn untrack_tester(pid: u32) {
+    TESTERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|&p| p != pid);
+}
+
+#[cfg(unix)]
+fn kill_tester(pid: u32) {
+    // Testers run in their own process group, which Ctrl-C does not reach
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_tester(_pid: u32) {}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::results::{RunResults, Verdict};
+
+/// Writes the run as a JUnit XML test suite with one test case per seed, so
+/// CI systems can show per-seed results. WA, RE and TLE become failures and
+/// the score is attached as a property and as the case's output.
+pub fn write_junit(run: &RunResults, path: &str) -> io::Result<()> {
+    let results = &run.results;
+    let failures = results.iter().filter(|r| r.verdict != Verdict::Ac).count();
+    let seconds = |ms: u64| format!("{:.3}", ms as f64 / 1000.0);
+    let total_ms: u64 = results.iter().map(|r| r.elapsed_ms).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"score_visualizer\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+        results.len(),
+        failures,
+        seconds(total_ms)
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"seeds\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{}\" timestamp=\"{}\">\n",
+        results.len(),
+        failures,
+        seconds(total_ms),
+        escape(&run.timestamp)
+    ));
+    xml.push_str(&format!(
+        "    <properties>\n      <property name=\"total_score\" value=\"{}\"/>\n    </properties>\n",
+        run.total_score
+    ));
+    for result in results {
+        let name = Path::new(&result.input_file).file_stem().map_or_else(
+            || result.seed.to_string(),
+            |s| s.to_string_lossy().to_string(),
+        );
+        xml.push_str(&format!(
+            "    <testcase classname=\"seeds\" name=\"{}\" time=\"{}\">\n",
+            escape(&name),
+            seconds(result.elapsed_ms)
+        ));
+        xml.push_str(&format!(
+            "      <properties>\n        <property name=\"score\" value=\"{}\"/>\n      </properties>\n",
+            escape(&result.score_string)
+        ));
+        let message = match result.verdict {
+            Verdict::Ac => None,
+            Verdict::Wa => Some("no score was reported"),
+            Verdict::Re => Some("the tester failed"),
+            Verdict::Tle => Some("time limit exceeded"),
+        };
+        if let Some(message) = message {
+            xml.push_str(&format!(
+                "      <failure type=\"{}\" message=\"{}\"/>\n",
+                result.verdict.as_str(),
+                message
+            ));
+        }
+        xml.push_str(&format!(
+            "      <system-out>Score = {}</system-out>\n    </testcase>\n",
+            escape(&result.score_string)
+        ));
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    fs::write(path, xml)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
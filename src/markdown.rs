@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+
+use crate::config::Config;
+use crate::progress::log;
+use crate::results::{RunResults, Verdict};
+use crate::scoring::Aggregate;
+
+/// How many of the worst seeds the summary lists.
+const WORST_SEEDS: usize = 10;
+/// How many of the most regressed seeds the step summary lists.
+const WORST_REGRESSIONS: usize = 10;
+
+/// Writes the markdown summary to `report.markdown_output`, if set.
+pub fn generate_markdown(run: &RunResults, baseline: Option<&RunResults>, config: &Config) {
+    let Some(path) = config
+        .report
+        .as_ref()
+        .and_then(|r| r.markdown_output.as_deref())
+    else {
+        return;
+    };
+    if let Err(e) = fs::write(path, summary_markdown(run, baseline)) {
+        log!("Error writing markdown summary: {}", e);
+    }
+}
+
+/// A short summary of the run for pull requests and chat: totals, verdicts
+/// and the worst seeds, with differences against `baseline` when given.
+pub fn summary_markdown(run: &RunResults, baseline: Option<&RunResults>) -> String {
+    let results = &run.results;
+    let mut md = String::from("## Score Results\n\n| | |\n|---|---|\n");
+
+    let mut total = run.headline_score();
+    if let Some(baseline) = baseline {
+        total.push_str(&format!(
+            " ({} vs baseline {})",
+            run.headline_delta(baseline).0,
+            run.headline_score_of(baseline)
+        ));
+    }
+    md.push_str(&format!("| {} | {} |\n", run.score_label(), total));
+    if !results.is_empty() && run.aggregate != Aggregate::Mean {
+        md.push_str(&format!(
+            "| Mean | {:.1} |\n",
+            run.total_score as f64 / results.len() as f64
+        ));
+    }
+    if let Some(total_relative) = run.total_relative_score {
+        md.push_str(&format!("| Relative | {} |\n", total_relative));
+    }
+    let verdicts: Vec<String> = Verdict::ALL
+        .iter()
+        .map(|&v| {
+            let count = results.iter().filter(|r| r.verdict == v).count();
+            format!("{} {}", v.as_str(), count)
+        })
+        .collect();
+    md.push_str(&format!(
+        "| Seeds | {} ({}) |\n",
+        results.len(),
+        verdicts.join(" / ")
+    ));
+    if let (Some(available), Some(extrapolated)) = (run.sampled_from, run.extrapolated_total()) {
+        let interval = run
+            .extrapolated_interval()
+            .map_or_else(String::new, |(low, high)| {
+                format!(" (95% CI {} to {})", low, high)
+            });
+        md.push_str(&format!(
+            "| Quick run | {} of {} seeds, extrapolated total {}{} |\n",
+            results.len(),
+            available,
+            extrapolated,
+            interval
+        ));
+    }
+    if run.interrupted {
+        md.push_str("| Partial | the run was stopped early |\n");
+    }
+    if let Some(run_id) = run.run_id {
+        md.push_str(&format!("| Run | #{} |\n", run_id));
+    }
+    if let Some(ref comment) = run.comment {
+        md.push_str(&format!("| Comment | {} |\n", comment.replace('|', "\\|")));
+    }
+    if !run.tags.is_empty() {
+        md.push_str(&format!("| Tags | {} |\n", run.tags.join(", ")));
+    }
+    md.push_str(&format!("| Timestamp (JST) | {} |\n", run.timestamp));
+    if let Some(ref git) = run.git {
+        md.push_str(&format!("| Commit | `{}` |\n", git.describe()));
+    }
+    if !run.params.is_empty() {
+        let params: Vec<String> = run
+            .params
+            .iter()
+            .map(|(name, value)| format!("`{}={}`", name, value))
+            .collect();
+        md.push_str(&format!("| Parameters | {} |\n", params.join(" ")));
+    }
+
+    let baseline_scores: Option<HashMap<usize, i64>> =
+        baseline.map(|b| b.results.iter().map(|r| (r.seed, r.score)).collect());
+    let mut worst: Vec<_> = results.iter().collect();
+    // Failed seeds first, whatever their score
+    worst.sort_by_key(|r| (r.verdict == Verdict::Ac, run.objective.gain(r.score)));
+    worst.truncate(WORST_SEEDS);
+    if !worst.is_empty() {
+        md.push_str(&format!("\n### Worst {} seeds\n\n", worst.len()));
+        if baseline_scores.is_some() {
+            md.push_str("| Seed | Score | Δ | Verdict |\n|---:|---:|---:|---|\n");
+        } else {
+            md.push_str("| Seed | Score | Verdict |\n|---:|---:|---|\n");
+        }
+        for result in worst {
+            let delta = match baseline_scores {
+                Some(ref scores) => match scores.get(&result.seed) {
+                    Some(&base) => format!(" {:+} |", result.score.saturating_sub(base)),
+                    None => " - |".to_string(),
+                },
+                None => String::new(),
+            };
+            md.push_str(&format!(
+                "| {} | {} |{} {} |\n",
+                result.seed,
+                result.score_string,
+                delta,
+                result.verdict.as_str()
+            ));
+        }
+    }
+    md
+}
+
+/// Appends the summary and the worst regressions against `baseline` to the
+/// GitHub Actions step summary: `path`, or else `$GITHUB_STEP_SUMMARY`.
+/// Nothing is written when neither is set.
+pub fn write_step_summary(
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+    path: Option<&str>,
+) -> io::Result<()> {
+    let path = match path.filter(|p| !p.is_empty()) {
+        Some(path) => path.to_string(),
+        None => match env::var("GITHUB_STEP_SUMMARY") {
+            Ok(path) if !path.is_empty() => path,
+            // Only an explicit --gha-summary needs somewhere to write
+            _ if path.is_some() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "GITHUB_STEP_SUMMARY is not set; pass a path to --gha-summary",
+                ))
+            }
+            _ => return Ok(()),
+        },
+    };
+    let mut md = summary_markdown(run, baseline);
+    if let Some(baseline) = baseline {
+        md.push_str(&regressions_markdown(run, baseline));
+    }
+    md.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(md.as_bytes())
+}
+
+/// The seeds that lost the most against `baseline`, worst first.
+fn regressions_markdown(run: &RunResults, baseline: &RunResults) -> String {
+    let baseline_scores: HashMap<usize, i64> =
+        baseline.results.iter().map(|r| (r.seed, r.score)).collect();
+    let mut regressions: Vec<(usize, i64, i64, i64)> = run
+        .results
+        .iter()
+        .filter_map(|r| {
+            let base = *baseline_scores.get(&r.seed)?;
+            let gain = run.objective.gain(r.score.saturating_sub(base));
+            (gain < 0).then_some((r.seed, base, r.score, gain))
+        })
+        .collect();
+    if regressions.is_empty() {
+        return "\nNo seed regressed against the baseline.\n".to_string();
+    }
+    regressions.sort_by_key(|&(_, _, _, gain)| gain);
+    let count = regressions.len();
+    regressions.truncate(WORST_REGRESSIONS);
+    let mut md = format!(
+        "\n### Worst regressions ({} of {} seeds regressed)\n\n| Seed | Baseline | Score | Δ |\n|---:|---:|---:|---:|\n",
+        regressions.len(),
+        count
+    );
+    for (seed, base, score, _) in regressions {
+        md.push_str(&format!(
+            "| {} | {} | {} | {:+} |\n",
+            seed,
+            base,
+            score,
+            score.saturating_sub(base)
+        ));
+    }
+    md
+}
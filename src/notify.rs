@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io;
+use std::time::Duration;
+
+use crate::results::{RunResults, Verdict};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Discord or Slack incoming webhook to post the summary of each run to
+    pub webhook_url: String,
+    /// Link to the report in the message instead of its local path, for
+    /// reports that are published somewhere
+    #[serde(default)]
+    pub report_url: Option<String>,
+}
+
+/// Posts a one-message summary of the finished run to the webhook.
+pub fn notify(
+    notify_config: &NotifyConfig,
+    run: &RunResults,
+    baseline: Option<&RunResults>,
+    report_path: &str,
+) -> io::Result<()> {
+    let results = &run.results;
+    let mut message = format!(
+        "Run finished: {} score {}",
+        run.score_label().to_lowercase(),
+        run.headline_score()
+    );
+    if let Some(baseline) = baseline {
+        message.push_str(&format!(
+            " ({} vs baseline {})",
+            run.headline_delta(baseline).0,
+            run.headline_score_of(baseline)
+        ));
+    }
+    let verdicts: Vec<String> = Verdict::ALL
+        .iter()
+        .map(|&v| {
+            let count = results.iter().filter(|r| r.verdict == v).count();
+            format!("{} {}", v.as_str(), count)
+        })
+        .collect();
+    message.push_str(&format!(
+        "\n{} seeds: {}",
+        results.len(),
+        verdicts.join(" / ")
+    ));
+    if run.interrupted {
+        message.push_str("\nThe run was stopped early; results are partial.");
+    }
+    let report = match notify_config.report_url {
+        Some(ref url) => url.clone(),
+        None => std::path::absolute(report_path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| report_path.to_string()),
+    };
+    message.push_str(&format!("\nReport: {}", report));
+
+    // Discord reads `content` and Slack reads `text`; each ignores the other
+    let body = json!({ "content": message, "text": message });
+    ureq::post(&notify_config.webhook_url)
+        .timeout(Duration::from_secs(10))
+        .send_json(body)
+        .map_err(io::Error::other)?;
+    Ok(())
+}
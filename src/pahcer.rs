@@ -0,0 +1,121 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+
+use crate::results::{Result, RunResults, Verdict};
+use crate::runner::format_score;
+
+/// A run in the result file format of pahcer (`pahcer/json/result_*.json`).
+#[derive(Serialize, Deserialize)]
+pub struct PahcerRun {
+    pub start_time: String,
+    pub case_count: usize,
+    pub total_score: i64,
+    #[serde(default)]
+    pub total_score_log10: f64,
+    #[serde(default)]
+    pub total_relative_score: f64,
+    #[serde(default)]
+    pub max_execution_time: f64,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub tag_name: Option<String>,
+    #[serde(default)]
+    pub wa_seeds: Vec<u64>,
+    pub cases: Vec<PahcerCase>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PahcerCase {
+    pub seed: u64,
+    pub score: i64,
+    /// Percent of the best known score, 100 being the best
+    #[serde(default)]
+    pub relative_score: f64,
+    /// Seconds
+    #[serde(default)]
+    pub execution_time: f64,
+    #[serde(default)]
+    pub error_message: String,
+}
+
+pub fn write_pahcer(run: &RunResults, path: &str) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(&PahcerRun::from(run))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, json)
+}
+
+impl From<&RunResults> for PahcerRun {
+    fn from(run: &RunResults) -> Self {
+        let cases: Vec<PahcerCase> = run
+            .results
+            .iter()
+            .map(|r| PahcerCase {
+                seed: r.seed as u64,
+                score: r.score,
+                // Our relative scores are AHC-style, 1e9 for the best
+                relative_score: r.relative_score.map_or(0.0, |s| s as f64 / 1e7),
+                execution_time: r.elapsed_ms as f64 / 1000.0,
+                error_message: match r.verdict {
+                    Verdict::Ac => String::new(),
+                    verdict => verdict.as_str().to_string(),
+                },
+            })
+            .collect();
+        let start_time = NaiveDateTime::parse_from_str(&run.timestamp, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .and_then(|t| Local.from_local_datetime(&t).single())
+            .map_or_else(|| run.timestamp.clone(), |t| t.to_rfc3339());
+        PahcerRun {
+            start_time,
+            case_count: cases.len(),
+            total_score: run.total_score,
+            total_score_log10: cases
+                .iter()
+                .filter(|c| c.score > 0)
+                .map(|c| (c.score as f64).log10())
+                .sum(),
+            total_relative_score: cases.iter().map(|c| c.relative_score).sum(),
+            max_execution_time: cases.iter().map(|c| c.execution_time).fold(0.0, f64::max),
+            comment: String::new(),
+            tag_name: None,
+            wa_seeds: run
+                .results
+                .iter()
+                .filter(|r| r.verdict != Verdict::Ac)
+                .map(|r| r.seed as u64)
+                .collect(),
+            cases,
+        }
+    }
+}
+
+impl From<PahcerRun> for RunResults {
+    fn from(pahcer: PahcerRun) -> Self {
+        let timestamp = DateTime::parse_from_rfc3339(&pahcer.start_time)
+            .map(|t| {
+                t.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or(pahcer.start_time);
+        let results = pahcer
+            .cases
+            .into_iter()
+            .map(|case| {
+                let failed = !case.error_message.is_empty() || pahcer.wa_seeds.contains(&case.seed);
+                Result {
+                    seed: case.seed as usize,
+                    score: case.score,
+                    score_string: format_score(case.score),
+                    elapsed_ms: (case.execution_time * 1000.0).round() as u64,
+                    verdict: if failed { Verdict::Wa } else { Verdict::Ac },
+                    ..Result::empty(&format!("{:04}.txt", case.seed), "")
+                }
+            })
+            .collect();
+        RunResults::new(timestamp, pahcer.total_score, results)
+    }
+}
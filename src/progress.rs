@@ -0,0 +1,70 @@
+//! The progress bars of a run, and printing that keeps clear of them and
+//! goes to the run log.
+
+use indicatif::MultiProgress;
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+
+static BARS: Mutex<Option<MultiProgress>> = Mutex::new(None);
+static RUN_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+/// `eprintln!` that clears the progress bars of a running run out of the
+/// way first, so the line is not drawn over or into them, and copies the
+/// line to the run log.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::progress::print(&format!($($arg)*))
+    };
+}
+pub(crate) use log;
+
+/// Makes `log!` print around `bars` until the guard is dropped.
+pub struct BarsGuard;
+
+impl BarsGuard {
+    pub fn new(bars: &MultiProgress) -> Self {
+        *BARS.lock().unwrap_or_else(|e| e.into_inner()) = Some(bars.clone());
+        BarsGuard
+    }
+}
+
+impl Drop for BarsGuard {
+    fn drop(&mut self) {
+        *BARS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Makes `log!` also write to `file` until the guard is dropped.
+pub struct RunLogGuard;
+
+impl RunLogGuard {
+    pub fn new(file: File) -> Self {
+        *RUN_LOG.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+        RunLogGuard
+    }
+}
+
+impl Drop for RunLogGuard {
+    fn drop(&mut self) {
+        *RUN_LOG.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Runs `f` with the bars of the current run, if any, hidden.
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    // Not held across `f`, which may print from several threads at once
+    let bars = BARS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match bars {
+        Some(bars) => bars.suspend(f),
+        None => f(),
+    }
+}
+
+/// What `log!` expands to.
+pub fn print(line: &str) {
+    suspend(|| eprintln!("{}", line));
+    if let Some(ref mut file) = *RUN_LOG.lock().unwrap_or_else(|e| e.into_inner()) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::git::GitInfo;
+use crate::pahcer::PahcerRun;
+use crate::progress::log;
+
+use crate::runner::extract_number;
+use crate::scoring::{Aggregate, Objective};
+use crate::stats::mean_confidence_interval;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Verdict {
+    /// The tester exited normally and a score was found
+    #[default]
+    Ac,
+    /// The tester exited normally but no score could be parsed
+    Wa,
+    /// The tester could not be run or exited with a non-zero status
+    Re,
+    /// The tester was killed for exceeding `tester.time_limit_ms`
+    Tle,
+}
+
+impl Verdict {
+    pub const ALL: [Verdict; 4] = [Verdict::Ac, Verdict::Wa, Verdict::Re, Verdict::Tle];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Ac => "AC",
+            Verdict::Wa => "WA",
+            Verdict::Re => "RE",
+            Verdict::Tle => "TLE",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Verdict> {
+        Verdict::ALL.into_iter().find(|v| v.as_str() == s)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Result {
+    #[serde(default)]
+    pub seed: usize,
+    pub input_file: String,
+    #[serde(default)]
+    pub output_file: String,
+    /// Input parameters read according to `[features]`
+    #[serde(default)]
+    pub features: BTreeMap<String, f64>,
+    pub score: i64,
+    pub score_string: String,
+    /// AHC-style relative score against the best known score for the seed
+    #[serde(default)]
+    pub relative_score: Option<u64>,
+    /// Best score recorded for the seed, from the best score store or
+    /// `scoring.best_scores`
+    #[serde(default)]
+    pub best_known_score: Option<i64>,
+    /// Wall-clock time of the tester invocation in milliseconds
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// Peak resident set size in kilobytes, where the platform reports it
+    #[serde(default)]
+    pub peak_rss_kb: Option<u64>,
+    #[serde(default)]
+    pub verdict: Verdict,
+    /// Values captured by `tester.metrics`, keyed by metric name
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>,
+    /// How many times the tester was run, counting `tester.retries`
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    pub visualizer: String,
+    /// Snapshot of the visualization, relative to the report like `visualizer`
+    #[serde(default)]
+    pub thumbnail: String,
+    /// Saved tester stderr, under `paths.logs_dir`
+    #[serde(default)]
+    pub stderr_file: String,
+    /// Solver stderr the tester command wrote to `{{solver_stderr}}`
+    #[serde(default)]
+    pub solver_stderr_file: String,
+    /// The case's command line and exit code, under `paths.logs_dir`
+    #[serde(default)]
+    pub case_file: String,
+}
+
+fn default_attempts() -> u32 {
+    1
+}
+
+impl Result {
+    /// A zero-score result for a case that could not be run.
+    pub fn empty(input_file: &str, output_file: &str) -> Self {
+        Result {
+            seed: extract_number(input_file),
+            input_file: input_file.to_string(),
+            output_file: output_file.to_string(),
+            features: BTreeMap::new(),
+            score: 0,
+            score_string: "0".to_string(),
+            relative_score: None,
+            best_known_score: None,
+            elapsed_ms: 0,
+            peak_rss_kb: None,
+            verdict: Verdict::Re,
+            metrics: BTreeMap::new(),
+            attempts: 1,
+            visualizer: String::new(),
+            thumbnail: String::new(),
+            stderr_file: String::new(),
+            solver_stderr_file: String::new(),
+            case_file: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RunResults {
+    pub timestamp: String,
+    #[serde(default)]
+    pub objective: Objective,
+    #[serde(default)]
+    pub num_cases: usize,
+    pub total_score: i64,
+    /// How the headline score combines the seeds' scores
+    #[serde(default)]
+    pub aggregate: Aggregate,
+    /// Seed to weight in the headline score, from `scoring.weights`; other
+    /// seeds weigh 1
+    #[serde(default)]
+    pub weights: BTreeMap<usize, f64>,
+    #[serde(default)]
+    pub total_relative_score: Option<u64>,
+    /// Number of available inputs when only a random sample of them was run
+    #[serde(default)]
+    pub sampled_from: Option<usize>,
+    /// Set when `--seeds` narrowed the run down to some of the seeds
+    #[serde(default)]
+    pub subset: bool,
+    /// Set when the run was stopped (Ctrl-C or too many failures) before
+    /// every seed finished
+    #[serde(default)]
+    pub interrupted: bool,
+    /// The `[params]` values the solver ran with
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    /// Commit of the solver the run started with
+    #[serde(default)]
+    pub git: Option<GitInfo>,
+    /// Id of the run in the history database
+    #[serde(default)]
+    pub run_id: Option<i64>,
+    /// Free text given with `run -m`
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// This run's directory under `paths.logs_dir`
+    #[serde(default)]
+    pub logs_dir: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub results: Vec<Result>,
+}
+
+impl RunResults {
+    pub fn new(timestamp: String, total_score: i64, results: Vec<Result>) -> Self {
+        RunResults {
+            timestamp,
+            objective: Objective::default(),
+            num_cases: results.len(),
+            total_score,
+            aggregate: Aggregate::default(),
+            weights: BTreeMap::new(),
+            total_relative_score: None,
+            sampled_from: None,
+            subset: false,
+            interrupted: false,
+            params: BTreeMap::new(),
+            git: None,
+            run_id: None,
+            comment: None,
+            logs_dir: None,
+            tags: Vec::new(),
+            results,
+        }
+    }
+
+    /// Whether the run left seeds out, so its total does not compare with a
+    /// full run's.
+    pub fn partial(&self) -> bool {
+        self.sampled_from.is_some() || self.subset
+    }
+
+    /// Names the headline score, e.g. "Weighted Mean" for "Weighted Mean Score".
+    pub fn score_label(&self) -> String {
+        self.aggregate.score_label(&self.weights)
+    }
+
+    /// The headline score: `total_score`, or the scores combined as
+    /// `aggregate` and `weights` say.
+    pub fn headline_score(&self) -> String {
+        self.headline_score_of(self)
+    }
+
+    /// The headline score of `run`, e.g. a baseline, combined the way this
+    /// run's is so the two compare.
+    pub fn headline_score_of(&self, run: &RunResults) -> String {
+        match self.combined_score(run) {
+            Some(score) => format!("{:.*}", self.precision(), score),
+            None => run.total_score.to_string(),
+        }
+    }
+
+    /// This run's headline score minus `baseline`'s, and the sign of the
+    /// difference.
+    pub fn headline_delta(&self, baseline: &RunResults) -> (String, i64) {
+        match (self.combined_score(self), self.combined_score(baseline)) {
+            (Some(score), Some(baseline_score)) => {
+                let delta = score - baseline_score;
+                let sign = match delta.partial_cmp(&0.0) {
+                    Some(std::cmp::Ordering::Greater) => 1,
+                    Some(std::cmp::Ordering::Less) => -1,
+                    _ => 0,
+                };
+                (format!("{:+.*}", self.precision(), delta), sign)
+            }
+            _ => {
+                let delta = self.total_score.saturating_sub(baseline.total_score);
+                (format!("{:+}", delta), delta.signum())
+            }
+        }
+    }
+
+    /// `run`'s scores combined this run's way, or None for a plain sum,
+    /// which `total_score` holds exactly.
+    fn combined_score(&self, run: &RunResults) -> Option<f64> {
+        self.combine(&run.results)
+    }
+
+    /// `results`, e.g. the seeds two runs share, combined this run's way, or
+    /// None for a plain sum.
+    pub fn combine(&self, results: &[Result]) -> Option<f64> {
+        self.aggregate.combine(results, &self.weights)
+    }
+
+    /// Decimals shown for the headline score; sums stay whole.
+    pub fn precision(&self) -> usize {
+        self.aggregate.precision()
+    }
+
+    /// The (weighted) total scaled up to all available inputs, for sampled runs.
+    pub fn extrapolated_total(&self) -> Option<i64> {
+        let available = self.sampled_from?;
+        if self.results.is_empty() {
+            return None;
+        }
+        let total = if self.weights.is_empty() {
+            self.total_score as f64
+        } else {
+            Aggregate::Sum.apply(&self.results, &self.weights)
+        };
+        let scale = available as f64 / self.results.len() as f64;
+        Some((total * scale).round() as i64)
+    }
+
+    /// 95% confidence interval of the extrapolated total, from the t
+    /// distribution of the sampled seeds' (weighted) scores with the finite
+    /// population correction for sampling without replacement.
+    pub fn extrapolated_interval(&self) -> Option<(i64, i64)> {
+        let available = self.sampled_from?;
+        let scores: Vec<f64> = self
+            .results
+            .iter()
+            .map(|r| self.weights.get(&r.seed).copied().unwrap_or(1.0) * r.score as f64)
+            .collect();
+        let (mean, half_width) = mean_confidence_interval(&scores)?;
+        let (n, total) = (scores.len() as f64, available as f64);
+        let correction = if total > 1.0 {
+            ((total - n).max(0.0) / (total - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        let half_width = half_width * correction;
+        Some((
+            ((mean - half_width) * total).round() as i64,
+            ((mean + half_width) * total).round() as i64,
+        ))
+    }
+}
+
+/// Sums scores, saturating with a warning instead of wrapping on overflow.
+pub fn total_score(results: &[Result]) -> i64 {
+    let mut total: i64 = 0;
+    for result in results {
+        total = match total.checked_add(result.score) {
+            Some(sum) => sum,
+            None => {
+                log!("[WARN] Total score overflowed i64; the total is saturated");
+                return if result.score > 0 { i64::MAX } else { i64::MIN };
+            }
+        };
+    }
+    total
+}
+
+/// Where the JSON results of a run are written. Unless `paths.results_json`
+/// is set they are stored next to the HTML report, which is also where
+/// `report` looks for them.
+pub fn results_path(config: &Config) -> PathBuf {
+    match config.paths.results_json {
+        Some(ref path) => PathBuf::from(path),
+        None => Path::new(&config.paths.html_output).with_file_name("results.json"),
+    }
+}
+
+pub fn save_results(path: &Path, run: &RunResults) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(run)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, json)
+}
+
+/// Reads results written by `save_results`, or a pahcer result file.
+pub fn load_results(path: &Path) -> io::Result<RunResults> {
+    let json = fs::read_to_string(path)?;
+    match serde_json::from_str(&json) {
+        Ok(run) => Ok(run),
+        Err(e) => match serde_json::from_str::<PahcerRun>(&json) {
+            Ok(pahcer) => Ok(pahcer.into()),
+            Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        },
+    }
+}
+
+/// One seed's score in a JSON file from another harness.
+#[derive(Deserialize)]
+struct SeedScore {
+    seed: usize,
+    score: i64,
+}
+
+/// Reads per-seed scores produced by another harness: a `seed,score` CSV
+/// (header lines and any further columns are skipped) or a JSON array of
+/// `{"seed": .., "score": ..}` objects. Our own and pahcer's result files are
+/// accepted as well.
+pub fn load_seed_scores(path: &Path) -> io::Result<Vec<(usize, i64)>> {
+    let text = fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "csv") {
+        let scores = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(',').map(str::trim);
+                let seed = fields.next()?.parse().ok()?;
+                let score = fields.next()?.parse().ok()?;
+                Some((seed, score))
+            })
+            .collect();
+        return Ok(scores);
+    }
+    match serde_json::from_str::<Vec<SeedScore>>(&text) {
+        Ok(scores) => Ok(scores.into_iter().map(|s| (s.seed, s.score)).collect()),
+        Err(e) => match load_results(path) {
+            Ok(run) => Ok(run
+                .results
+                .iter()
+                .map(|r| (extract_number(&r.input_file), r.score))
+                .collect()),
+            Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        },
+    }
+}
+
+pub fn write_csv(results: &[Result], path: &str) -> io::Result<()> {
+    let mut csv = String::from("seed,score,time,visualizer\n");
+    for result in results {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            result.seed,
+            result.score,
+            result.elapsed_ms,
+            csv_field(&result.visualizer)
+        ));
+    }
+    fs::write(path, csv)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,690 @@
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, IoMode, ScoreSource, ScorerConfig};
+use crate::features::parse_features;
+use crate::interrupt::{interrupted, track_tester, untrack_tester};
+use crate::progress::log;
+use crate::results::{Result, Verdict};
+use crate::seeds::SeedFilter;
+
+/// Lists the `.txt` inputs in `dir`, keeping only the seeds in `seeds` if given.
+pub fn get_input_files(dir: &str, seeds: Option<&SeedFilter>) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "txt")
+            && seeds.is_none_or(|seeds| seeds.contains(extract_number(&path.to_string_lossy())))
+        {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(files)
+}
+
+pub fn extract_number(filename: &str) -> usize {
+    let base = Path::new(filename).file_name().unwrap().to_string_lossy();
+    let parts: Vec<&str> = base.split('.').collect();
+    if !parts.is_empty() {
+        parts[0].parse::<usize>().unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+pub fn format_score(score: i64) -> String {
+    format!("{}", score)
+}
+
+fn parse_score_from_output(output: &str) -> Option<i64> {
+    for line in output.lines() {
+        let line = line.trim();
+        // Try "Score = X" format
+        if let Some(score_str) = line.strip_prefix("Score = ") {
+            if let Ok(score) = score_str.trim().parse::<i64>() {
+                return Some(score);
+            }
+        }
+        // Try just a number
+        if let Ok(score) = line.parse::<i64>() {
+            return Some(score);
+        }
+    }
+    None
+}
+
+/// Finds the score in tester output, either with the configured regex or by
+/// looking for the last `Score = X` line.
+fn parse_tester_score(output: &str, re: Option<&Regex>) -> Option<i64> {
+    if let Some(re) = re {
+        return parse_score_with_regex(output, re);
+    }
+    let mut score = None;
+    for line in output.lines() {
+        if let Some(score_str) = line.strip_prefix("Score = ") {
+            score = score_str.parse::<i64>().ok();
+        }
+    }
+    score
+}
+
+/// Returns the first capture group of the last match of `re` in `output`.
+fn parse_score_with_regex(output: &str, re: &Regex) -> Option<i64> {
+    re.captures_iter(output)
+        .filter_map(|caps| caps.get(1)?.as_str().trim().parse::<i64>().ok())
+        .last()
+}
+
+/// Returns the first capture group of the last match of `re` as a number.
+fn parse_metric(output: &str, re: &Regex) -> Option<f64> {
+    re.captures_iter(output)
+        .filter_map(|caps| caps.get(1)?.as_str().trim().parse::<f64>().ok())
+        .last()
+}
+
+fn run_scorer(input_file: &str, output_file: &str, scorer_config: &ScorerConfig) -> Option<i64> {
+    // Convert to absolute paths so the command works regardless of working directory
+    let abs_input =
+        fs::canonicalize(input_file).unwrap_or_else(|_| Path::new(input_file).to_path_buf());
+    let abs_output =
+        fs::canonicalize(output_file).unwrap_or_else(|_| Path::new(output_file).to_path_buf());
+
+    let parts = command_args(
+        &scorer_config.command,
+        &[
+            ("input", &abs_input.to_string_lossy()),
+            ("output", &abs_output.to_string_lossy()),
+        ],
+    )?;
+
+    let mut cmd = Command::new(&parts[0]);
+    cmd.args(&parts[1..])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if let Some(ref dir) = scorer_config.working_dir {
+        cmd.current_dir(dir);
+    }
+
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) => {
+            log!("Error running scorer: {}", e);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log!(
+            "Scorer failed for {} {}: {}",
+            input_file,
+            output_file,
+            stderr
+        );
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_score_from_output(&stdout)
+}
+
+/// Where the solver output for `input_file` is saved.
+pub fn output_path(output_dir: &str, input_file: &str) -> String {
+    let base_name = Path::new(input_file)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    format!("{}/{}", output_dir, base_name)
+}
+
+/// Runs one case, retrying up to `tester.retries` times while the tester
+/// fails or prints no score. Timeouts are not retried.
+pub fn process_file(
+    input_file: &str,
+    output_dir: &str,
+    config: &Config,
+    tools_dir: &Path,
+) -> Result {
+    let mut attempt = 1;
+    loop {
+        let mut result = run_case(input_file, output_dir, config, tools_dir);
+        result.attempts = attempt;
+        if let Some(ref logs_dir) = config.paths.logs_dir {
+            result = link_logs(result, logs_dir);
+        }
+        let failed = matches!(result.verdict, Verdict::Re | Verdict::Wa);
+        if !failed || attempt > config.tester.retries || interrupted() {
+            return result;
+        }
+        attempt += 1;
+        log!(
+            "[WARN] Retrying {} (attempt {} of {})",
+            input_file,
+            attempt,
+            config.tester.retries + 1
+        );
+    }
+}
+
+/// Creates a new directory under `logs_dir` for the logs of one run or
+/// trial, named after the start time and process id. An existing directory
+/// is never reused.
+pub fn create_logs_dir(logs_dir: &str) -> io::Result<String> {
+    fs::create_dir_all(logs_dir)?;
+    let name = format!(
+        "{}-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    );
+    let mut dir = format!("{}/{}", logs_dir, name);
+    let mut n = 1;
+    loop {
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            // Another run of this process, as under `watch`, in the same second
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                n += 1;
+                dir = format!("{}/{}-{}", logs_dir, name, n);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn stderr_log_path(logs_dir: &str, input_file: &str) -> String {
+    let stem = Path::new(input_file)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    format!("{}/{}.stderr.txt", logs_dir, stem)
+}
+
+/// The command line a case ran and how it ended, for `<seed>.case.txt`.
+fn case_log(args: &[String], status: &ExitStatus, timed_out: bool, elapsed_ms: u64) -> String {
+    let exit = match status.code() {
+        Some(code) => code.to_string(),
+        None => "none (killed by a signal)".to_string(),
+    };
+    let timed_out = if timed_out {
+        " (time limit exceeded)"
+    } else {
+        ""
+    };
+    format!(
+        "command: {}\nexit code: {}\nelapsed: {} ms{}\n",
+        shell_words::join(args),
+        exit,
+        elapsed_ms,
+        timed_out
+    )
+}
+
+/// Records the logs `run_case` left in `logs_dir` for this case.
+fn link_logs(mut result: Result, logs_dir: &str) -> Result {
+    let stderr_file = stderr_log_path(logs_dir, &result.input_file);
+    let solver_stderr_file = stderr_file.replace(".stderr.txt", ".solver.stderr.txt");
+    let case_file = stderr_file.replace(".stderr.txt", ".case.txt");
+    if Path::new(&stderr_file).is_file() {
+        result.stderr_file = stderr_file;
+    }
+    if Path::new(&solver_stderr_file).is_file() {
+        result.solver_stderr_file = solver_stderr_file;
+    }
+    if Path::new(&case_file).is_file() {
+        result.case_file = case_file;
+    }
+    result
+}
+
+/// Replaces each `{{name}}` in `template` with its value.
+fn expand_placeholders(template: &str, placeholders: &[(&str, &str)]) -> String {
+    placeholders
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{{{}}}}}", name), value)
+        })
+}
+
+/// Splits a command template into arguments with shell-style quoting, then
+/// fills in the placeholders of each argument so values containing spaces
+/// stay one argument.
+pub fn command_args(template: &str, placeholders: &[(&str, &str)]) -> Option<Vec<String>> {
+    match shell_words::split(template) {
+        Ok(args) if !args.is_empty() => Some(
+            args.iter()
+                .map(|arg| expand_placeholders(arg, placeholders))
+                .collect(),
+        ),
+        Ok(_) => None,
+        Err(e) => {
+            log!("Error parsing command `{}`: {}", template, e);
+            None
+        }
+    }
+}
+
+/// A command that runs `command` through the platform shell.
+pub fn shell_command(command: &str) -> Command {
+    let args = shell_args(command);
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    cmd
+}
+
+/// The arguments running `command` through the platform shell.
+fn shell_args(command: &str) -> Vec<String> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    vec![shell.to_string(), flag.to_string(), command.to_string()]
+}
+
+fn quote_for_shell(value: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", value)
+    } else {
+        shell_words::quote(value).into_owned()
+    }
+}
+
+fn run_case(input_file: &str, output_dir: &str, config: &Config, tools_dir: &Path) -> Result {
+    let base_name = Path::new(input_file)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let output_file = output_path(output_dir, input_file);
+    let stem = base_name.trim_end_matches(".txt");
+    let tools_dir = tools_dir.to_string_lossy();
+    let solver_stderr = config
+        .paths
+        .logs_dir
+        .as_ref()
+        .map(|dir| format!("{}/{}.solver.stderr.txt", dir, stem));
+    if let Some(ref path) = solver_stderr {
+        // Left over from an earlier run, it would pass for this one's
+        let _ = fs::remove_file(path);
+    }
+    let mut placeholders = vec![
+        ("input_file", input_file),
+        ("output_file", output_file.as_str()),
+        ("seed", stem),
+        ("tools_dir", &tools_dir),
+    ];
+    if let Some(script) = config.tester.script.as_deref() {
+        placeholders.push(("script", script));
+    }
+    if let Some(solver_script) = config.tester.solver_script.as_deref() {
+        placeholders.push(("solver_script", solver_script));
+    }
+    if let Some(ref solver_stderr) = solver_stderr {
+        placeholders.push(("solver_stderr", solver_stderr));
+    }
+    let params = config.param_values();
+    let param_names: Vec<String> = params
+        .keys()
+        .map(|name| format!("param:{}", name))
+        .collect();
+    for (name, value) in param_names.iter().zip(params.values()) {
+        placeholders.push((name, value));
+    }
+
+    // Open input file
+    let input_data = match fs::read(input_file) {
+        Ok(data) => data,
+        Err(e) => {
+            log!("Error reading input file: {}", e);
+            return Result::empty(input_file, &output_file);
+        }
+    };
+
+    let features = config
+        .features
+        .as_ref()
+        .map(|f| parse_features(&String::from_utf8_lossy(&input_data), f))
+        .unwrap_or_default();
+
+    // Run tester command
+    let mut args = if config.tester.use_shell {
+        // Quote the values so paths with spaces survive the shell
+        let quoted: Vec<(&str, String)> = placeholders
+            .iter()
+            .map(|&(name, value)| (name, quote_for_shell(value)))
+            .collect();
+        let quoted: Vec<(&str, &str)> = quoted.iter().map(|(n, v)| (*n, v.as_str())).collect();
+        shell_args(&expand_placeholders(&config.tester.command, &quoted))
+    } else {
+        let Some(parts) = command_args(&config.tester.command, &placeholders) else {
+            return Result::empty(input_file, &output_file);
+        };
+        parts
+    };
+    // An interactive tester is given the solver command and talks to it over pipes;
+    // the input still goes to the tester's stdin and the answer comes from its stdout
+    if let Some(tester) = config.tester.interactive_tester() {
+        let Some(mut tester_args) = command_args(tester, &placeholders) else {
+            return Result::empty(input_file, &output_file);
+        };
+        tester_args.append(&mut args);
+        args = tester_args;
+    }
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    let files_mode = config.tester.io_mode == IoMode::Files;
+    let writes_output_file =
+        !config.tester.interactive && config.tester.command.contains("{{output_file}}");
+    if (files_mode || writes_output_file) && config.tester.solution_file.is_none() {
+        // Left over from an earlier run, it would be scored if the tester wrote nothing
+        let _ = fs::remove_file(&output_file);
+    }
+    // Parameters are exported as is; `tester.env` may override them
+    cmd.envs(&params)
+        .envs(
+            config
+                .tester
+                .env
+                .iter()
+                .map(|(name, value)| (name, expand_placeholders(value, &placeholders))),
+        )
+        .stdin(if files_mode {
+            Stdio::null()
+        } else {
+            Stdio::piped()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Run the tester in its own process group so a TLE can kill the solver it spawned too
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let start = Instant::now();
+    let child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            log!("Error starting tester: {}", e);
+            return Result::empty(input_file, &output_file);
+        }
+    };
+    let pid = child.id();
+    track_tester(pid);
+
+    // Feed the input and collect the output, killing the tester if it exceeds the time limit
+    let time_limit = config.tester.time_limit_ms.map(Duration::from_millis);
+    let waited = wait_with_timeout(child, input_data, time_limit);
+    untrack_tester(pid);
+    let TesterRun {
+        output,
+        timed_out,
+        peak_rss_kb,
+    } = match waited {
+        Ok(run) => run,
+        Err(e) => {
+            log!("Error waiting for tester: {}", e);
+            return Result::empty(input_file, &output_file);
+        }
+    };
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    if let Some(ref logs_dir) = config.paths.logs_dir {
+        let log = stderr_log_path(logs_dir, input_file);
+        if let Err(e) = fs::write(&log, &output.stderr) {
+            log!("Error writing {}: {}", log, e);
+        }
+        let case = log.replace(".stderr.txt", ".case.txt");
+        if let Err(e) = fs::write(
+            &case,
+            case_log(&args, &output.status, timed_out, elapsed_ms),
+        ) {
+            log!("Error writing {}: {}", case, e);
+        }
+    }
+
+    if let (Some(rss_kb), Some(limit_mb)) = (peak_rss_kb, config.tester.memory_warning_mb) {
+        if rss_kb > limit_mb * 1024 {
+            log!(
+                "[WARN] {} used {} MB, above memory_warning_mb = {}",
+                input_file,
+                rss_kb / 1024,
+                limit_mb
+            );
+        }
+    }
+
+    // Save the solution: the tester's stdout, or the file it wrote the answer to
+    match config.tester.solution_file {
+        Some(ref template) => {
+            let solution_file = expand_placeholders(template, &placeholders);
+            if let Err(e) = fs::copy(&solution_file, &output_file) {
+                log!("Error copying solution file {}: {}", solution_file, e);
+            }
+        }
+        // A tester given {{output_file}} writes the solution there itself
+        None if files_mode || writes_output_file => {
+            if !Path::new(&output_file).is_file() {
+                log!("[WARN] No solution written to {}", output_file);
+            }
+        }
+        None => {
+            let _ = fs::write(&output_file, &output.stdout);
+        }
+    }
+
+    if timed_out {
+        log!(
+            "[WARN] TLE for {}: killed after {} ms",
+            input_file,
+            elapsed_ms
+        );
+        return Result {
+            features,
+            elapsed_ms,
+            peak_rss_kb,
+            verdict: Verdict::Tle,
+            ..Result::empty(input_file, &output_file)
+        };
+    }
+
+    let stderr_string = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        log!(
+            "[WARN] Tester failed for {}: exit code {:?}",
+            input_file,
+            output.status.code()
+        );
+        log!("[WARN] stderr: {}", stderr_string);
+    }
+
+    let stdout_string = String::from_utf8_lossy(&output.stdout);
+
+    // Get score: use separate scorer if configured, otherwise parse from the tester's output
+    let score = if let Some(ref scorer) = config.scorer {
+        run_scorer(input_file, &output_file, scorer)
+    } else {
+        // Validated in load_config
+        let re = config
+            .tester
+            .score_regex
+            .as_ref()
+            .map(|pattern| Regex::new(pattern).expect("invalid tester.score_regex"));
+        let from_stderr = || parse_tester_score(&stderr_string, re.as_ref());
+        let from_stdout = || parse_tester_score(&stdout_string, re.as_ref());
+        match config.tester.score_source() {
+            ScoreSource::Stderr => from_stderr(),
+            ScoreSource::Stdout => from_stdout(),
+            ScoreSource::Both => from_stderr().or_else(from_stdout),
+        }
+    };
+
+    // A crash takes precedence over a missing score; a parsed score of 0 is still AC
+    let verdict = if !output.status.success() {
+        Verdict::Re
+    } else if score.is_none() {
+        Verdict::Wa
+    } else {
+        Verdict::Ac
+    };
+    let score = score.unwrap_or(0);
+
+    // Extra metrics logged by the solver, searched in stderr then stdout
+    let mut metrics = BTreeMap::new();
+    for metric in &config.tester.metrics {
+        // Validated in load_config
+        let re = Regex::new(&metric.regex).expect("invalid tester.metrics regex");
+        let value = parse_metric(&stderr_string, &re).or_else(|| parse_metric(&stdout_string, &re));
+        if let Some(value) = value {
+            metrics.insert(metric.name.clone(), value);
+        }
+    }
+
+    Result {
+        features,
+        score,
+        score_string: format_score(score),
+        elapsed_ms,
+        peak_rss_kb,
+        verdict,
+        metrics,
+        ..Result::empty(input_file, &output_file)
+    }
+}
+
+/// What came back from a single tester invocation.
+struct TesterRun {
+    output: Output,
+    timed_out: bool,
+    /// Peak resident set size of the tester and the processes it waited for
+    peak_rss_kb: Option<u64>,
+}
+
+/// Writes `input` to the child's stdin and waits for it to exit. With a time
+/// limit the whole process group is killed once it is exceeded, and
+/// `timed_out` is set.
+fn wait_with_timeout(
+    mut child: Child,
+    input: Vec<u8>,
+    time_limit: Option<Duration>,
+) -> io::Result<TesterRun> {
+    // Pipes are serviced on their own threads so a solver that never reads
+    // its input or floods its output cannot block the deadline check.
+    let stdin = child.stdin.take();
+    let writer = thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&input);
+        }
+    });
+    let stdout_reader = spawn_reader(child.stdout.take());
+    let stderr_reader = spawn_reader(child.stderr.take());
+
+    let mut timed_out = false;
+    let (status, peak_rss_kb) = match time_limit {
+        None => wait_child(&mut child, true)?.expect("blocking wait returned no status"),
+        Some(limit) => {
+            let deadline = Instant::now() + limit;
+            loop {
+                if let Some(exit) = wait_child(&mut child, false)? {
+                    break exit;
+                }
+                if Instant::now() >= deadline {
+                    timed_out = true;
+                    kill_process_group(&mut child);
+                    break wait_child(&mut child, true)?.expect("blocking wait returned no status");
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    };
+
+    let _ = writer.join();
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    Ok(TesterRun {
+        output: Output {
+            status,
+            stdout,
+            stderr,
+        },
+        timed_out,
+        peak_rss_kb,
+    })
+}
+
+/// Reaps the child with `wait4` so its resource usage can be read. Returns
+/// `None` if `block` is false and the child is still running.
+#[cfg(unix)]
+fn wait_child(child: &mut Child, block: bool) -> io::Result<Option<(ExitStatus, Option<u64>)>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let flags = if block { 0 } else { libc::WNOHANG };
+    let mut status = 0;
+    // SAFETY: rusage is plain old data and wait4 only writes into the pointers given
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, flags, &mut usage) };
+        if pid > 0 {
+            break;
+        }
+        if pid == 0 {
+            return Ok(None);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    // ru_maxrss is in kilobytes on Linux but in bytes on macOS
+    let max_rss = usage.ru_maxrss as u64;
+    let peak_rss_kb = if cfg!(target_os = "macos") {
+        max_rss / 1024
+    } else {
+        max_rss
+    };
+    Ok(Some((ExitStatus::from_raw(status), Some(peak_rss_kb))))
+}
+
+#[cfg(not(unix))]
+fn wait_child(child: &mut Child, block: bool) -> io::Result<Option<(ExitStatus, Option<u64>)>> {
+    let status = if block {
+        Some(child.wait()?)
+    } else {
+        child.try_wait()?
+    };
+    Ok(status.map(|status| (status, None)))
+}
+
+fn spawn_reader<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    // The tester was started with process_group(0), so its pid is the group id
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
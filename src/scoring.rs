@@ -0,0 +1,297 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+
+use crate::config::Config;
+use crate::progress::log;
+use crate::results::{Result, Verdict};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Objective {
+    #[serde(alias = "minimize")]
+    #[value(alias = "minimize")]
+    Min,
+    #[default]
+    #[serde(alias = "maximize")]
+    #[value(alias = "maximize")]
+    Max,
+}
+
+impl Objective {
+    /// Turns a score difference into a gain, positive when it is an improvement.
+    pub fn gain(self, delta: i64) -> i64 {
+        match self {
+            Objective::Max => delta,
+            Objective::Min => -delta,
+        }
+    }
+
+    pub fn best<'a>(self, results: impl Iterator<Item = &'a Result>) -> Option<&'a Result> {
+        match self {
+            Objective::Max => results.max_by_key(|r| r.score),
+            Objective::Min => results.min_by_key(|r| r.score),
+        }
+    }
+
+    pub fn worst<'a>(self, results: impl Iterator<Item = &'a Result>) -> Option<&'a Result> {
+        match self {
+            Objective::Max => results.min_by_key(|r| r.score),
+            Objective::Min => results.max_by_key(|r| r.score),
+        }
+    }
+
+    /// Seeds of the `k` worst results.
+    pub fn worst_seeds(self, results: &[Result], k: usize) -> HashSet<usize> {
+        let mut ranked: Vec<&Result> = results.iter().collect();
+        ranked.sort_by_key(|r| self.gain(r.score));
+        ranked.iter().take(k).map(|r| r.seed).collect()
+    }
+}
+
+/// How the seeds' scores combine into the run's headline score.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Mean,
+    /// Sum of the base-10 logarithms of the scores
+    LogSum,
+    GeometricMean,
+}
+
+impl Aggregate {
+    /// Combines the scores of `results`, each weighted by its seed's entry in
+    /// `weights` (1 if absent); means divide by the total weight. Logarithms
+    /// count scores below 1 as 1, so a failed seed adds nothing rather than
+    /// minus infinity.
+    pub fn apply(self, results: &[Result], weights: &BTreeMap<usize, f64>) -> f64 {
+        let weight = |r: &Result| weights.get(&r.seed).copied().unwrap_or(1.0);
+        let total_weight: f64 = results.iter().map(weight).sum();
+        let sum = || {
+            results
+                .iter()
+                .map(|r| weight(r) * r.score as f64)
+                .sum::<f64>()
+        };
+        let log_sum = || {
+            results
+                .iter()
+                .map(|r| weight(r) * (r.score.max(1) as f64).log10())
+                .sum::<f64>()
+        };
+        match self {
+            Aggregate::Sum => sum(),
+            Aggregate::LogSum => log_sum(),
+            _ if total_weight == 0.0 => 0.0,
+            Aggregate::Mean => sum() / total_weight,
+            Aggregate::GeometricMean => 10f64.powf(log_sum() / total_weight),
+        }
+    }
+
+    /// Names the headline score, e.g. "Mean" for "Mean Score".
+    pub fn label(self) -> &'static str {
+        match self {
+            Aggregate::Sum => "Total",
+            Aggregate::Mean => "Mean",
+            Aggregate::LogSum => "Log",
+            Aggregate::GeometricMean => "Geometric Mean",
+        }
+    }
+
+    /// Decimals shown for the headline score; sums stay whole.
+    pub fn precision(self) -> usize {
+        match self {
+            Aggregate::Sum => 0,
+            _ => 3,
+        }
+    }
+
+    /// `results` combined with `weights`, or None for a plain sum, which
+    /// the integer total holds exactly.
+    pub fn combine(self, results: &[Result], weights: &BTreeMap<usize, f64>) -> Option<f64> {
+        (self != Aggregate::Sum || !weights.is_empty()).then(|| self.apply(results, weights))
+    }
+
+    /// Names the headline score, e.g. "Weighted Mean" for "Weighted Mean Score".
+    pub fn score_label(self, weights: &BTreeMap<usize, f64>) -> String {
+        if weights.is_empty() {
+            self.label().to_string()
+        } else {
+            format!("Weighted {}", self.label())
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoringConfig {
+    #[serde(default)]
+    pub objective: Objective,
+    /// How the headline score combines the seeds' scores; the report's
+    /// total, baseline delta and summaries use it
+    #[serde(default)]
+    pub aggregate: Aggregate,
+    /// JSON object mapping seed to its weight in the headline score and the
+    /// extrapolated total; seeds not listed weigh 1
+    #[serde(default)]
+    pub weights: Option<String>,
+    /// Seed count of the system test, e.g. 2000; the report then estimates
+    /// the system test total from the run's mean
+    #[serde(default)]
+    pub system_test_seeds: Option<usize>,
+    /// JSON object mapping each rival to their total or per-seed scores, to
+    /// estimate the run's rank in the report's standings
+    #[serde(default)]
+    pub rivals: Option<String>,
+    /// JSON object mapping seed to the best known score for that seed, e.g.
+    /// from the leaderboard; takes precedence over `paths.best_scores`
+    #[serde(default)]
+    pub best_scores: Option<String>,
+}
+
+pub fn load_best_scores(path: &str) -> io::Result<HashMap<usize, i64>> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// The seed weights in `scoring.weights`, empty when none are configured
+/// or they cannot be read.
+pub fn load_weights(config: &Config) -> BTreeMap<usize, f64> {
+    let Some(path) = config.scoring.as_ref().and_then(|s| s.weights.as_ref()) else {
+        return BTreeMap::new();
+    };
+    let loaded = fs::read_to_string(path).and_then(|json| {
+        serde_json::from_str(&json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    });
+    match loaded {
+        Ok(weights) => weights,
+        Err(e) => {
+            log!("Error loading weights from {}: {}", path, e);
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Writes seed to best score as a JSON object, seeds in order.
+pub fn save_best_scores(path: &str, best_scores: &HashMap<usize, i64>) -> io::Result<()> {
+    let sorted: BTreeMap<usize, i64> = best_scores.iter().map(|(&k, &v)| (k, v)).collect();
+    let json = serde_json::to_string_pretty(&sorted)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, json)
+}
+
+/// AHC-style relative score in `[0, 1e9]`. A score that beats the best known
+/// one counts as the new best, so it gets the full 1e9.
+pub fn relative_score(score: i64, best: Option<i64>, objective: Objective) -> u64 {
+    // Relative scores are only defined for positive scores
+    if score <= 0 {
+        return 0;
+    }
+    let best = match (best, objective) {
+        (Some(best), Objective::Min) if best > 0 => best.min(score),
+        (Some(best), Objective::Max) => best.max(score),
+        _ => score,
+    };
+    let ratio = match objective {
+        Objective::Min => best as f64 / score as f64,
+        Objective::Max => score as f64 / best as f64,
+    };
+    (1e9 * ratio).round() as u64
+}
+
+/// The per-seed best score store kept up to date after each run:
+/// `paths.best_scores`, or `best_scores.json` in `paths.answers_dir`.
+pub fn best_scores_store(config: &Config) -> Option<String> {
+    match (&config.paths.best_scores, &config.paths.answers_dir) {
+        (Some(path), _) => Some(path.clone()),
+        (None, Some(answers_dir)) => Some(format!("{}/best_scores.json", answers_dir)),
+        (None, None) => None,
+    }
+}
+
+/// Reads the store, which does not exist until the first run saves it.
+pub fn load_best_scores_store(path: &str) -> io::Result<HashMap<usize, i64>> {
+    match load_best_scores(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        scores => scores,
+    }
+}
+
+/// Records every accepted result that beats the best score of its seed, or
+/// is the first for it, returning the seeds that improved.
+pub fn update_best_scores(
+    best_scores: &mut HashMap<usize, i64>,
+    results: &[Result],
+    objective: Objective,
+) -> Vec<usize> {
+    let mut improved = Vec::new();
+    for result in results {
+        // A failed seed's score says nothing about what is achievable
+        if result.verdict != Verdict::Ac {
+            continue;
+        }
+        let better = best_scores.get(&result.seed).is_none_or(|&best| {
+            let delta = result.score.saturating_sub(best);
+            objective.gain(delta) > 0
+        });
+        if better {
+            best_scores.insert(result.seed, result.score);
+            improved.push(result.seed);
+        }
+    }
+    improved
+}
+
+/// The best score known to be reachable on the result's seed: its best known
+/// score, or the result itself when it was accepted and does better.
+pub fn potential_score(result: &Result, objective: Objective) -> Option<i64> {
+    let own = (result.verdict == Verdict::Ac).then_some(result.score);
+    match (result.best_known_score, own) {
+        (Some(best), Some(score)) => Some(if objective.gain(score.saturating_sub(best)) > 0 {
+            score
+        } else {
+            best
+        }),
+        (best, score) => best.or(score),
+    }
+}
+
+/// Fills in `best_known_score` and `relative_score` for every result,
+/// returning the relative total. The best scores come from
+/// `scoring.best_scores`, or else from `paths.best_scores`; without either
+/// there is nothing to score against.
+pub fn apply_relative_scores(results: &mut [Result], config: &Config) -> Option<u64> {
+    let objective = config
+        .scoring
+        .as_ref()
+        .map(|s| s.objective)
+        .unwrap_or_default();
+    let loaded = match config.scoring.as_ref().and_then(|s| s.best_scores.as_ref()) {
+        Some(path) => load_best_scores(path).map_err(|e| (path, e)),
+        None => {
+            let path = config.paths.best_scores.as_ref()?;
+            load_best_scores_store(path).map_err(|e| (path, e))
+        }
+    };
+    let best_scores = match loaded {
+        Ok(scores) => scores,
+        Err((path, e)) => {
+            log!("Error loading best scores from {}: {}", path, e);
+            return None;
+        }
+    };
+    let mut total = 0;
+    for result in results.iter_mut() {
+        result.best_known_score = best_scores.get(&result.seed).copied();
+        let relative = relative_score(result.score, result.best_known_score, objective);
+        result.relative_score = Some(relative);
+        total += relative;
+    }
+    Some(total)
+}
@@ -0,0 +1,146 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A set of seeds written as comma-separated numbers and inclusive ranges,
+/// e.g. `0-49,100,151`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SeedFilter {
+    ranges: Vec<(usize, usize)>,
+}
+
+impl SeedFilter {
+    pub fn contains(&self, seed: usize) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(start, end)| start <= seed && seed <= end)
+    }
+}
+
+impl FromStr for SeedFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("invalid seed '{}' in '{}'", n.trim(), s))
+        };
+        let mut ranges = Vec::new();
+        for part in s.split(',').filter(|p| !p.trim().is_empty()) {
+            let range = match part.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => {
+                    let seed = parse(part)?;
+                    (seed, seed)
+                }
+            };
+            if range.0 > range.1 {
+                return Err(format!("empty seed range '{}'", part.trim()));
+            }
+            ranges.push(range);
+        }
+        if ranges.is_empty() {
+            return Err("no seeds given".to_string());
+        }
+        Ok(SeedFilter { ranges })
+    }
+}
+
+impl TryFrom<String> for SeedFilter {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for SeedFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|&(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            })
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl From<SeedFilter> for String {
+    fn from(filter: SeedFilter) -> String {
+        filter.to_string()
+    }
+}
+
+/// Picks `count` input files at random, keeping their original order. With
+/// `rng_seed` the same files are picked every time.
+pub fn sample_inputs(files: &[String], count: usize, rng_seed: Option<u64>) -> Vec<String> {
+    let mut rng = make_rng(rng_seed);
+    let mut picked: Vec<usize> =
+        rand::seq::index::sample(&mut rng, files.len(), count.min(files.len())).into_vec();
+    picked.sort_unstable();
+    picked.into_iter().map(|i| files[i].clone()).collect()
+}
+
+/// Like `sample_inputs`, but splits the files into `strata` groups of equal
+/// size by feature value and samples each group in proportion to its size,
+/// so the sample covers the whole feature range. `values` holds the feature
+/// value of each file; files without one form a group of their own.
+pub fn stratified_sample(
+    files: &[String],
+    values: &[Option<f64>],
+    count: usize,
+    strata: usize,
+    rng_seed: Option<u64>,
+) -> Vec<String> {
+    let mut rng = make_rng(rng_seed);
+    let count = count.min(files.len());
+
+    let mut known: Vec<usize> = (0..files.len()).filter(|&i| values[i].is_some()).collect();
+    known.sort_by(|&a, &b| values[a].unwrap().total_cmp(&values[b].unwrap()));
+    let strata = strata.clamp(1, known.len().max(1));
+    let mut groups: Vec<Vec<usize>> = (0..strata)
+        .map(|k| known[k * known.len() / strata..(k + 1) * known.len() / strata].to_vec())
+        .collect();
+    groups.push((0..files.len()).filter(|&i| values[i].is_none()).collect());
+    groups.retain(|g| !g.is_empty());
+
+    // Largest-remainder allocation of `count` over the groups by size
+    let quotas: Vec<f64> = groups
+        .iter()
+        .map(|g| count as f64 * g.len() as f64 / files.len() as f64)
+        .collect();
+    let mut take: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..groups.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        (quotas[b] - quotas[b].floor()).total_cmp(&(quotas[a] - quotas[a].floor()))
+    });
+    let missing = count - take.iter().sum::<usize>();
+    for &g in by_remainder.iter().take(missing) {
+        take[g] += 1;
+    }
+
+    let mut picked: Vec<usize> = Vec::with_capacity(count);
+    for (group, n) in groups.iter().zip(take) {
+        let chosen = rand::seq::index::sample(&mut rng, group.len(), n.min(group.len()));
+        picked.extend(chosen.into_iter().map(|i| group[i]));
+    }
+    picked.sort_unstable();
+    picked.into_iter().map(|i| files[i].clone()).collect()
+}
+
+pub fn make_rng(rng_seed: Option<u64>) -> StdRng {
+    match rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
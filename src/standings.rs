@@ -0,0 +1,131 @@
+//! Estimated standings against the saved scores of rivals.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+
+use crate::results::{RunResults, Verdict};
+use crate::scoring::{relative_score, Objective};
+
+/// The name the current run is listed under.
+pub const YOU: &str = "(you)";
+
+/// What is known of a rival: a score per seed, or only a total.
+pub enum RivalScores {
+    Total(i64),
+    Seeds(HashMap<usize, i64>),
+}
+
+/// Reads a JSON object mapping each rival's name to either a total or an
+/// object mapping seed to score, e.g. `{"alice": {"0": 123}, "bob": 45678}`.
+pub fn load_rivals(path: &str) -> io::Result<BTreeMap<String, RivalScores>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let json = fs::read_to_string(path)?;
+    let rivals: BTreeMap<String, serde_json::Value> =
+        serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+    // Not an untagged enum: that would lose the seed keys' conversion from strings
+    rivals
+        .into_iter()
+        .map(|(name, value)| {
+            let scores = match value.as_i64() {
+                Some(total) => RivalScores::Total(total),
+                None => RivalScores::Seeds(
+                    serde_json::from_value(value)
+                        .map_err(|e| invalid(format!("{}: {}", name, e)))?,
+                ),
+            };
+            Ok((name, scores))
+        })
+        .collect()
+}
+
+/// One line of the standings.
+#[derive(Serialize)]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    /// Total over the seeds of the run; as saved for rivals with only a total
+    pub total: i64,
+    /// AHC-style relative score over the seeds of the run, against the best
+    /// score anyone listed got on each; only when every rival has per-seed
+    /// scores
+    pub relative: Option<u64>,
+}
+
+/// Ranks the run among `rivals`: by relative score when every rival has
+/// per-seed scores, otherwise by total. A seed a rival has no score for
+/// counts as 0 for them. Equal results share a rank.
+pub fn standings(run: &RunResults, rivals: &BTreeMap<String, RivalScores>) -> Vec<Standing> {
+    let objective = run.objective;
+    let per_seed = rivals
+        .values()
+        .all(|scores| matches!(scores, RivalScores::Seeds(_)));
+
+    // Seed to score of everyone on the run's seeds, the run first
+    let mine: HashMap<usize, i64> = run
+        .results
+        .iter()
+        .map(|r| (r.seed, if r.verdict == Verdict::Ac { r.score } else { 0 }))
+        .collect();
+    let mut entrants = vec![(YOU, Some(&mine), run.total_score)];
+    for (name, scores) in rivals {
+        entrants.push(match scores {
+            RivalScores::Total(total) => (name.as_str(), None, *total),
+            RivalScores::Seeds(scores) => {
+                let total = mine
+                    .keys()
+                    .filter_map(|seed| scores.get(seed))
+                    .fold(0i64, |sum, &score| sum.saturating_add(score));
+                (name.as_str(), Some(scores), total)
+            }
+        });
+    }
+
+    let best: HashMap<usize, i64> = mine
+        .keys()
+        .filter_map(|&seed| {
+            let scores = entrants
+                .iter()
+                .filter_map(|&(_, scores, _)| scores?.get(&seed))
+                .filter(|&&score| score > 0)
+                .copied();
+            let best = match objective {
+                Objective::Max => scores.max(),
+                Objective::Min => scores.min(),
+            };
+            Some((seed, best?))
+        })
+        .collect();
+    let mut standings: Vec<Standing> = entrants
+        .into_iter()
+        .map(|(name, scores, total)| Standing {
+            rank: 0,
+            name: name.to_string(),
+            total,
+            relative: scores.filter(|_| per_seed).map(|scores| {
+                mine.keys()
+                    .map(|seed| {
+                        let score = scores.get(seed).copied().unwrap_or(0);
+                        relative_score(score, best.get(seed).copied(), objective)
+                    })
+                    .sum()
+            }),
+        })
+        .collect();
+
+    // Higher relative scores always win; totals follow the objective
+    let key = |s: &Standing| match s.relative {
+        Some(relative) => relative as i128,
+        None => objective.gain(s.total) as i128,
+    };
+    standings.sort_by_key(|s| std::cmp::Reverse(key(s)));
+    for i in 0..standings.len() {
+        standings[i].rank = if i > 0 && key(&standings[i]) == key(&standings[i - 1]) {
+            standings[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+    standings
+}
@@ -0,0 +1,287 @@
+use crate::results::Result;
+
+/// Score statistics for the seeds whose feature value falls in one range.
+pub struct Bucket {
+    pub label: String,
+    pub count: usize,
+    pub mean: f64,
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Groups results by the value of `feature`. With at most `bucket_count`
+/// distinct values every value gets its own bucket, otherwise the value range
+/// is split into `bucket_count` equal-width ranges. Seeds without the feature
+/// are left out.
+pub fn group_by_feature(results: &[Result], feature: &str, bucket_count: usize) -> Vec<Bucket> {
+    let mut values: Vec<(f64, i64)> = results
+        .iter()
+        .filter_map(|r| Some((*r.features.get(feature)?, r.score)))
+        .collect();
+    if values.is_empty() || bucket_count == 0 {
+        return Vec::new();
+    }
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut distinct: Vec<f64> = values.iter().map(|&(v, _)| v).collect();
+    distinct.dedup();
+
+    let mut buckets = Vec::new();
+    if distinct.len() <= bucket_count {
+        for value in distinct {
+            let scores: Vec<i64> = values
+                .iter()
+                .filter(|&&(v, _)| v == value)
+                .map(|&(_, s)| s)
+                .collect();
+            buckets.push(make_bucket(format!("{} = {}", feature, value), &scores));
+        }
+    } else {
+        let lo = distinct[0];
+        let hi = distinct[distinct.len() - 1];
+        let width = (hi - lo) / bucket_count as f64;
+        for i in 0..bucket_count {
+            let start = lo + width * i as f64;
+            let end = if i + 1 == bucket_count {
+                hi
+            } else {
+                lo + width * (i + 1) as f64
+            };
+            // The last bucket is closed so the maximum value is included
+            let scores: Vec<i64> = values
+                .iter()
+                .filter(|&&(v, _)| v >= start && (v < end || (i + 1 == bucket_count && v <= end)))
+                .map(|&(_, s)| s)
+                .collect();
+            if scores.is_empty() {
+                continue;
+            }
+            let close = if i + 1 == bucket_count { "]" } else { ")" };
+            buckets.push(make_bucket(
+                format!("{} in [{:.0}, {:.0}{}", feature, start, end, close),
+                &scores,
+            ));
+        }
+    }
+    buckets
+}
+
+fn make_bucket(label: String, scores: &[i64]) -> Bucket {
+    Bucket {
+        label,
+        count: scores.len(),
+        mean: scores.iter().map(|&s| s as f64).sum::<f64>() / scores.len() as f64,
+        min: scores.iter().copied().min().unwrap_or(0),
+        max: scores.iter().copied().max().unwrap_or(0),
+    }
+}
+
+/// Descriptive statistics of one column of values.
+pub struct Summary {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p5: f64,
+    pub p95: f64,
+}
+
+pub fn summarize(values: &[f64]) -> Option<Summary> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / n;
+    let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    Some(Summary {
+        mean,
+        median: percentile(&sorted, 50.0),
+        stddev: variance.sqrt(),
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        p5: percentile(&sorted, 5.0),
+        p95: percentile(&sorted, 95.0),
+    })
+}
+
+/// Mean of `values` and the half-width of its two-sided 95% confidence
+/// interval, from Student's t distribution. `None` for fewer than two values.
+pub fn mean_confidence_interval(values: &[f64]) -> Option<(f64, f64)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    Some((
+        mean,
+        t_critical_95(values.len() - 1) * (variance / n).sqrt(),
+    ))
+}
+
+/// Two-sided 95% critical value of the t distribution: tabulated up to 30
+/// degrees of freedom, then a Cornish-Fisher expansion around the normal one.
+fn t_critical_95(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    if (1..=TABLE.len()).contains(&df) {
+        return TABLE[df - 1];
+    }
+    let z: f64 = 1.959964;
+    let df = df as f64;
+    z + (z.powi(3) + z) / (4.0 * df)
+        + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * df * df)
+}
+
+/// A run's mean score scaled up to the seed count of the system test.
+pub struct Extrapolation {
+    pub seeds: usize,
+    pub mean: f64,
+    /// Half-width of the 95% confidence interval of the mean
+    pub half_width: f64,
+    /// The seeds furthest from the mean when together they make up most of
+    /// the variance, so a handful of cases sway the estimate; else empty
+    pub dominant_seeds: Vec<usize>,
+    /// Their share of the variance
+    pub dominant_share: f64,
+}
+
+/// Share of the variance above which the most extreme seeds are flagged.
+const DOMINANT_SHARE: f64 = 0.5;
+
+/// Extrapolates the mean score of `results` to `seeds` seeds. The most
+/// extreme twentieth of the seeds (at least one) are flagged when they
+/// account for more than half of the variance.
+pub fn extrapolate(results: &[Result], seeds: usize) -> Option<Extrapolation> {
+    let scores: Vec<f64> = results.iter().map(|r| r.score as f64).collect();
+    let (mean, half_width) = mean_confidence_interval(&scores)?;
+    let mut deviations: Vec<(f64, usize)> = results
+        .iter()
+        .map(|r| ((r.score as f64 - mean).powi(2), r.seed))
+        .collect();
+    deviations.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let total: f64 = deviations.iter().map(|d| d.0).sum();
+    let extreme = &deviations[..(deviations.len() / 20).max(1)];
+    let share = if total > 0.0 {
+        extreme.iter().map(|d| d.0).sum::<f64>() / total
+    } else {
+        0.0
+    };
+    Some(Extrapolation {
+        seeds,
+        mean,
+        half_width,
+        dominant_seeds: if share > DOMINANT_SHARE {
+            extreme.iter().map(|d| d.1).collect()
+        } else {
+            Vec::new()
+        },
+        dominant_share: share,
+    })
+}
+
+/// Linearly interpolated percentile of already sorted values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Two-sided p-value of the paired Wilcoxon signed-rank test on per-seed
+/// score differences: how likely differences this lopsided are if neither
+/// run is actually better. Zero differences are dropped. Small samples
+/// without tied magnitudes get the exact distribution, others the normal
+/// approximation with tie and continuity corrections. `None` when every
+/// difference is zero.
+pub fn wilcoxon_signed_rank(differences: &[i64]) -> Option<f64> {
+    let mut magnitudes: Vec<(u64, bool)> = differences
+        .iter()
+        .filter(|&&d| d != 0)
+        .map(|&d| (d.unsigned_abs(), d > 0))
+        .collect();
+    if magnitudes.is_empty() {
+        return None;
+    }
+    magnitudes.sort_unstable();
+    let n = magnitudes.len();
+
+    // Tied magnitudes share the average of their ranks
+    let mut w_plus = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < n {
+        let mut end = start;
+        while end < n && magnitudes[end].0 == magnitudes[start].0 {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        w_plus += rank * magnitudes[start..end].iter().filter(|m| m.1).count() as f64;
+        let t = (end - start) as f64;
+        tie_correction += t * t * t - t;
+        start = end;
+    }
+
+    let p = if tie_correction == 0.0 && n <= EXACT_WILCOXON_MAX {
+        exact_wilcoxon(n, w_plus as usize)
+    } else {
+        let n = n as f64;
+        let mean = n * (n + 1.0) / 4.0;
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+        let z = ((w_plus - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+        erfc(z / std::f64::consts::SQRT_2)
+    };
+    Some(p.min(1.0))
+}
+
+/// Largest sample the exact signed-rank distribution is computed for.
+const EXACT_WILCOXON_MAX: usize = 50;
+
+/// Two-sided p-value of `w_plus` under the exact null distribution of the
+/// signed-rank statistic for `n` untied differences.
+fn exact_wilcoxon(n: usize, w_plus: usize) -> f64 {
+    // probability[s]: chance that the positive ranks sum to s
+    let max_sum = n * (n + 1) / 2;
+    let mut probability = vec![0.0; max_sum + 1];
+    probability[0] = 1.0;
+    for rank in 1..=n {
+        for s in (0..=max_sum).rev() {
+            let with_rank = if s >= rank {
+                probability[s - rank]
+            } else {
+                0.0
+            };
+            probability[s] = (probability[s] + with_rank) / 2.0;
+        }
+    }
+    let lower: f64 = probability[..=w_plus].iter().sum();
+    let upper: f64 = probability[w_plus..].iter().sum();
+    2.0 * lower.min(upper)
+}
+
+/// Complementary error function, accurate to about 1e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
@@ -0,0 +1,280 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Parameter grids and ranges for the `sweep` command.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// Values to try for each parameter: with "grid" every combination is
+    /// run, with "random" each trial picks one at random
+    #[serde(default)]
+    pub params: BTreeMap<String, Vec<ParamValue>>,
+    /// Ranges sampled by the "random" strategy
+    #[serde(default)]
+    pub ranges: BTreeMap<String, ParamRange>,
+    /// How many parameter sets the "random" strategy or `optimize` tries
+    #[serde(default = "default_trials")]
+    pub trials: usize,
+    /// CSV with the parameters and totals of every combination
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Trials of `optimize`, kept so a later session resumes from the best
+    /// one (default: .ahc_optimize.json)
+    #[serde(default)]
+    pub optimize_history: Option<String>,
+}
+
+fn default_trials() -> usize {
+    20
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Every combination of `params`
+    #[default]
+    Grid,
+    /// `trials` parameter sets drawn from `params` and `ranges`
+    Random,
+}
+
+/// A parameter range, drawn from uniformly or, with `log`, log-uniformly.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamRange {
+    pub min: f64,
+    pub max: f64,
+    /// Round to whole numbers
+    #[serde(default)]
+    pub int: bool,
+    /// Uniform in the logarithm, for values spanning orders of magnitude
+    #[serde(default)]
+    pub log: bool,
+}
+
+impl ParamRange {
+    /// Why the range cannot be sampled, if it cannot.
+    pub fn problem(&self) -> Option<&'static str> {
+        if self.min > self.max {
+            Some("min is above max")
+        } else if self.log && self.min <= 0.0 {
+            Some("a log range must be positive")
+        } else if self.int && self.min.ceil() > self.max.floor() {
+            Some("an int range must hold a whole number")
+        } else {
+            None
+        }
+    }
+
+    /// Whether the range holds more than one value.
+    fn varies(&self) -> bool {
+        if self.int {
+            self.min.ceil() < self.max.floor()
+        } else {
+            self.min < self.max
+        }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> String {
+        self.value_at(rng.gen_range(0.0..=1.0))
+    }
+
+    /// The value at `position` between min (0) and max (1), on the log scale
+    /// for log ranges.
+    fn value_at(&self, position: f64) -> String {
+        let position = position.clamp(0.0, 1.0);
+        let value = if self.log {
+            (self.min.ln() + (self.max.ln() - self.min.ln()) * position).exp()
+        } else {
+            self.min + (self.max - self.min) * position
+        };
+        if self.int {
+            format!(
+                "{}",
+                value.round().clamp(self.min.ceil(), self.max.floor()) as i64
+            )
+        } else {
+            format_float(value)
+        }
+    }
+
+    /// Where `value` lies in the range, the inverse of `value_at`.
+    fn position(&self, value: &str) -> Option<f64> {
+        let value: f64 = value.parse().ok()?;
+        let position = if self.log {
+            (value.ln() - self.min.ln()) / (self.max.ln() - self.min.ln())
+        } else {
+            (value - self.min) / (self.max - self.min)
+        };
+        Some(if position.is_finite() { position } else { 0.0 })
+    }
+}
+
+/// Six significant digits, without an exponent so any solver can parse it.
+fn format_float(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let decimals = (5 - magnitude).max(0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+/// One value of a parameter, written in the config as a number or a string.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Int(v) => write!(f, "{}", v),
+            ParamValue::Float(v) => write!(f, "{}", v),
+            ParamValue::Str(v) => f.write_str(v),
+        }
+    }
+}
+
+/// One parameter set for the "random" strategy: a random listed value for
+/// each of `params` and a random draw from each of `ranges`.
+pub fn random_params(sweep: &SweepConfig, rng: &mut impl Rng) -> BTreeMap<String, String> {
+    let mut params = BTreeMap::new();
+    for (name, values) in &sweep.params {
+        if let Some(value) = values.choose(rng) {
+            params.insert(name.clone(), value.to_string());
+        }
+    }
+    for (name, range) in &sweep.ranges {
+        params.insert(name.clone(), range.sample(rng));
+    }
+    params
+}
+
+/// Whether `optimize` has anything to vary: a range wider than a point or a
+/// parameter with a choice of values.
+pub fn can_vary(sweep: &SweepConfig) -> bool {
+    sweep.params.values().any(|values| {
+        values
+            .iter()
+            .any(|v| v.to_string() != values[0].to_string())
+    }) || sweep.ranges.values().any(ParamRange::varies)
+}
+
+/// Draws `neighbour` makes before giving up on moving.
+const NEIGHBOUR_ATTEMPTS: usize = 1000;
+
+/// A neighbour of `current` for the hill climb of `optimize`. Each parameter
+/// changes with probability 1/(number of parameters), and at least one
+/// does: ranges move by a normal step of `step` (as a fraction of the range)
+/// and listed values switch to another one at random. `None` when no draw
+/// moved away from `current`.
+pub fn neighbour(
+    sweep: &SweepConfig,
+    current: &BTreeMap<String, String>,
+    step: f64,
+    rng: &mut impl Rng,
+) -> Option<BTreeMap<String, String>> {
+    let dims = (sweep.params.len() + sweep.ranges.len()).max(1) as f64;
+    for _ in 0..NEIGHBOUR_ATTEMPTS {
+        let mut next = current.clone();
+        for (name, values) in &sweep.params {
+            if values.len() > 1 && rng.gen_bool(1.0 / dims) {
+                let others: Vec<String> = values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .filter(|v| current.get(name) != Some(v))
+                    .collect();
+                if let Some(value) = others.choose(rng) {
+                    next.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        for (name, range) in &sweep.ranges {
+            if range.varies() && rng.gen_bool(1.0 / dims) {
+                let position = current
+                    .get(name)
+                    .and_then(|value| range.position(value))
+                    .unwrap_or(0.5);
+                next.insert(name.clone(), range.value_at(position + step * normal(rng)));
+            }
+        }
+        // Small steps on int ranges often round back to the same value
+        if next != *current {
+            return Some(next);
+        }
+    }
+    None
+}
+
+/// A standard normal draw (Box-Muller).
+fn normal(rng: &mut impl Rng) -> f64 {
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let v: f64 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+}
+
+/// Every combination of the grid's values, the last parameter varying
+/// fastest. A parameter with no values leaves nothing to run.
+pub fn grid(params: &BTreeMap<String, Vec<ParamValue>>) -> Vec<BTreeMap<String, String>> {
+    let mut combinations = vec![BTreeMap::new()];
+    for (name, values) in params {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.insert(name.clone(), value.to_string());
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int_range(min: f64, max: f64) -> SweepConfig {
+        SweepConfig {
+            strategy: Strategy::Grid,
+            params: BTreeMap::new(),
+            ranges: BTreeMap::from([(
+                "k".to_string(),
+                ParamRange {
+                    min,
+                    max,
+                    int: true,
+                    log: false,
+                },
+            )]),
+            trials: 1,
+            output: None,
+            optimize_history: None,
+        }
+    }
+
+    #[test]
+    fn int_range_without_a_whole_number_is_invalid() {
+        assert!(int_range(1.2, 1.8).ranges["k"].problem().is_some());
+    }
+
+    #[test]
+    fn int_range_with_one_whole_number_cannot_vary() {
+        let sweep = int_range(1.0, 1.5);
+        assert!(sweep.ranges["k"].problem().is_none());
+        assert!(!can_vary(&sweep));
+        let current = BTreeMap::from([("k".to_string(), "1".to_string())]);
+        assert!(neighbour(&sweep, &current, 0.5, &mut rand::thread_rng()).is_none());
+    }
+}
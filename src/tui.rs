@@ -0,0 +1,301 @@
+use std::io::{self, Stdout};
+use std::time::Duration;
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table, TableState};
+use ratatui::Terminal;
+
+use crate::interrupt;
+use crate::results::{Result, Verdict};
+use crate::runner::format_score;
+use crate::scoring::Objective;
+
+/// Full-screen view of a run in progress: a scrollable table of finished
+/// seeds with the running total, best/worst seed and failures.
+pub struct Tui {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    objective: Objective,
+    num_cases: usize,
+    results: Vec<Result>,
+    table: TableState,
+    follow: bool,
+    active: bool,
+    stderr: StderrCapture,
+}
+
+impl Tui {
+    pub fn start(num_cases: usize, objective: Objective) -> io::Result<Tui> {
+        // Warnings from the tester would scribble over the screen
+        let stderr = StderrCapture::start()?;
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        let mut tui = Tui {
+            terminal,
+            objective,
+            num_cases,
+            results: Vec::with_capacity(num_cases),
+            table: TableState::default(),
+            follow: true,
+            active: true,
+            stderr,
+        };
+        tui.draw(false)?;
+        Ok(tui)
+    }
+
+    pub fn push(&mut self, result: &Result) -> io::Result<()> {
+        self.results.push(result.clone());
+        if self.follow {
+            self.table.select(Some(self.results.len() - 1));
+        }
+        self.draw(false)
+    }
+
+    /// Handles key presses for up to `timeout`.
+    pub fn poll(&mut self, timeout: Duration) -> io::Result<()> {
+        if !event::poll(timeout)? {
+            return Ok(());
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                return Ok(());
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                // Raw mode swallows SIGINT, so pass it on by hand
+                if interrupt::interrupted() {
+                    self.restore();
+                }
+                interrupt::request();
+            }
+            self.scroll(key.code);
+        }
+        self.draw(false)
+    }
+
+    /// Keeps the final table on screen until the user quits, then restores
+    /// the terminal and replays anything written to stderr meanwhile.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.draw(true)?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    code => self.scroll(code),
+                }
+                self.draw(true)?;
+            }
+        }
+        self.restore();
+        Ok(())
+    }
+
+    fn restore(&mut self) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        self.stderr.stop();
+    }
+
+    fn scroll(&mut self, code: KeyCode) {
+        let last = self.results.len().saturating_sub(1);
+        let selected = self.table.selected().unwrap_or(0);
+        let page = self.terminal.size().map_or(10, |s| s.height as usize / 2);
+        let next = match code {
+            KeyCode::Up | KeyCode::Char('k') => selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => selected + 1,
+            KeyCode::PageUp => selected.saturating_sub(page),
+            KeyCode::PageDown => selected + page,
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => last,
+            _ => return,
+        };
+        let next = next.min(last);
+        // Jumping back to the newest row resumes following new results
+        self.follow = next == last;
+        self.table.select(Some(next));
+    }
+
+    fn draw(&mut self, done: bool) -> io::Result<()> {
+        let total: i64 = self
+            .results
+            .iter()
+            .fold(0i64, |acc, r| acc.saturating_add(r.score));
+        let best = self.objective.best(self.results.iter());
+        let worst = self.objective.worst(self.results.iter());
+        let failures: Vec<&Result> = self
+            .results
+            .iter()
+            .filter(|r| r.verdict != Verdict::Ac)
+            .collect();
+
+        let seed_score = |r: Option<&Result>| {
+            r.map_or("-".to_string(), |r| {
+                format!("{} (seed {})", format_score(r.score), r.seed)
+            })
+        };
+        let summary = vec![
+            Line::from(format!("Total: {}", format_score(total))),
+            Line::from(format!("Best:  {}", seed_score(best))),
+            Line::from(format!("Worst: {}", seed_score(worst))),
+            Line::from(format!("Failures: {}", failures.len())),
+        ];
+        let failure_lines: Vec<Line> = failures
+            .iter()
+            .map(|r| Line::from(format!("{:>5} {}", r.seed, r.verdict.as_str())))
+            .collect();
+
+        let rows = self.results.iter().map(|r| {
+            let style = match r.verdict {
+                Verdict::Ac => Style::default(),
+                _ => Style::default().fg(Color::Red),
+            };
+            Row::new(vec![
+                Cell::from(r.seed.to_string()),
+                Cell::from(r.verdict.as_str()),
+                Cell::from(format_score(r.score)),
+                Cell::from(r.elapsed_ms.to_string()),
+            ])
+            .style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Length(16),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(vec!["Seed", "Verdict", "Score", "Time (ms)"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL).title("Results"));
+
+        let done_count = self.results.len();
+        let ratio = if self.num_cases == 0 {
+            1.0
+        } else {
+            done_count as f64 / self.num_cases as f64
+        };
+        let gauge_label = format!("{}/{}", done_count, self.num_cases);
+        let help = if done {
+            "Done. ↑/↓ PgUp/PgDn Home/End to scroll, q to exit"
+        } else {
+            "↑/↓ PgUp/PgDn Home/End to scroll, Ctrl-C to stop"
+        };
+
+        let table_state = &mut self.table;
+        self.terminal.draw(|frame| {
+            let [gauge_area, top, body, help_area] = Layout::vertical([
+                Constraint::Length(3),
+                Constraint::Length(6),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ])
+            .areas(frame.area());
+            let [summary_area, failures_area] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(top);
+
+            frame.render_widget(
+                Gauge::default()
+                    .block(Block::default().borders(Borders::ALL).title("Progress"))
+                    .gauge_style(Style::default().fg(Color::Cyan))
+                    .ratio(ratio.min(1.0))
+                    .label(gauge_label),
+                gauge_area,
+            );
+            frame.render_widget(
+                Paragraph::new(summary).block(Block::default().borders(Borders::ALL).title("Run")),
+                summary_area,
+            );
+            frame.render_widget(
+                Paragraph::new(failure_lines)
+                    .block(Block::default().borders(Borders::ALL).title("Failures")),
+                failures_area,
+            );
+            frame.render_stateful_widget(table, body, table_state);
+            frame.render_widget(Paragraph::new(help), help_area);
+        })?;
+        Ok(())
+    }
+}
+
+/// Redirects the process's stderr into a temporary file while the TUI owns
+/// the terminal, and writes the captured text back out when stopped.
+struct StderrCapture {
+    #[cfg(unix)]
+    saved: Option<(i32, std::fs::File)>,
+}
+
+impl StderrCapture {
+    #[cfg(unix)]
+    fn start() -> io::Result<StderrCapture> {
+        use std::os::unix::io::AsRawFd;
+
+        let path =
+            std::env::temp_dir().join(format!("score_visualizer-{}.log", std::process::id()));
+        let file = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let _ = std::fs::remove_file(&path);
+        let saved = unsafe { libc::dup(libc::STDERR_FILENO) };
+        if saved < 0 || unsafe { libc::dup2(file.as_raw_fd(), libc::STDERR_FILENO) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(StderrCapture {
+            saved: Some((saved, file)),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn start() -> io::Result<StderrCapture> {
+        Ok(StderrCapture {})
+    }
+
+    #[cfg(unix)]
+    fn stop(&mut self) {
+        use std::io::{Read, Seek, SeekFrom, Write};
+
+        if let Some((saved, mut file)) = self.saved.take() {
+            unsafe {
+                libc::dup2(saved, libc::STDERR_FILENO);
+                libc::close(saved);
+            }
+            let mut captured = String::new();
+            if file.seek(SeekFrom::Start(0)).is_ok() && file.read_to_string(&mut captured).is_ok() {
+                let _ = io::stderr().write_all(captured.as_bytes());
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn stop(&mut self) {}
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
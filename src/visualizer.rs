@@ -0,0 +1,290 @@
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+use crate::config::{Config, VisualizerConfig};
+use crate::progress::log;
+use crate::results::Result;
+use crate::runner::command_args;
+
+/// Used when `[visualizer]` has no command: the official tools' `vis` binary.
+pub const DEFAULT_COMMAND: &str = "./target/release/vis {{input_file}} {{output_file}}";
+/// File name the official `vis` writes its HTML to.
+const DEFAULT_VIS_OUTPUT: &str = "vis.html";
+
+/// Used when thumbnails are on but `thumbnail_command` is not set.
+pub const DEFAULT_THUMBNAIL_COMMAND: &str = "chromium --headless --disable-gpu --hide-scrollbars \
+     --window-size=800,800 --screenshot={{thumbnail_file}} file://{{vis_file}}";
+
+/// Held while a visualizer runs in the shared `working_dir`.
+static SHARED_WORKING_DIR: Mutex<()> = Mutex::new(());
+
+/// Size of the visualizer pool: `parallel.vis_threads`, else `num_threads`,
+/// else the number of CPUs.
+pub fn vis_threads(config: &Config) -> usize {
+    config
+        .parallel
+        .as_ref()
+        .and_then(|p| p.vis_threads.or(p.num_threads))
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+}
+
+/// Visualizes the results `select` picks on a pool of `vis_threads`; the
+/// rest are returned without a visualization.
+pub fn visualize_selected(
+    results: Vec<Result>,
+    config: &Config,
+    select: impl Fn(&Result) -> bool + Sync,
+) -> Vec<Result> {
+    let tools_dir = Path::new(&config.paths.input_dir)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(vis_threads(config))
+        .build()
+        .unwrap();
+    pool.install(|| {
+        results
+            .into_par_iter()
+            .map(|mut result| {
+                result.visualizer = String::new();
+                result.thumbnail = String::new();
+                if !select(&result) {
+                    return result;
+                }
+                visualize_result(
+                    result,
+                    &config.paths.output_dir,
+                    &config.paths.visualizer_dir,
+                    &tools_dir,
+                    config,
+                )
+            })
+            .collect()
+    })
+}
+
+pub fn visualize_result(
+    mut result: Result,
+    output_dir: &str,
+    visualizer_dir: &str,
+    _tools_dir: &Path,
+    config: &Config,
+) -> Result {
+    // Check if visualizer is disabled
+    if let Some(ref vis_config) = config.visualizer {
+        if !vis_config.enabled {
+            return result;
+        }
+    }
+
+    let base_name = Path::new(&result.input_file)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let visualizer_file = format!("{}/{}", visualizer_dir, base_name.replace(".txt", ".html"));
+
+    let abs_input = fs::canonicalize(&result.input_file)
+        .unwrap_or_else(|_| Path::new(&result.input_file).to_path_buf());
+    let output_path = format!("{}/{}", output_dir, base_name);
+    let abs_output =
+        fs::canonicalize(&output_path).unwrap_or_else(|_| Path::new(&output_path).to_path_buf());
+
+    let vis_config = config.visualizer.as_ref();
+    let template = vis_config
+        .and_then(|v| v.command.as_deref())
+        .unwrap_or(DEFAULT_COMMAND);
+    let abs_vis_output = std::path::absolute(&visualizer_file)
+        .unwrap_or_else(|_| Path::new(&visualizer_file).to_path_buf());
+    let Some(parts) = command_args(
+        template,
+        &[
+            ("input_file", &abs_input.to_string_lossy()),
+            ("output_file", &abs_output.to_string_lossy()),
+            ("vis_output", &abs_vis_output.to_string_lossy()),
+            // Older names for input_file and output_file
+            ("input", &abs_input.to_string_lossy()),
+            ("output", &abs_output.to_string_lossy()),
+        ],
+    ) else {
+        return result;
+    };
+    let working_dir = vis_config.and_then(|v| v.working_dir.as_deref());
+    let writes_vis_output = template.contains("{{vis_output}}");
+
+    // Visualizations run in parallel. A visualizer that writes a fixed file
+    // name gets a scratch directory of its own, unless it needs to run in
+    // `working_dir`, where runs have to take turns.
+    let scratch_dir = if writes_vis_output || working_dir.is_some() {
+        None
+    } else {
+        let dir = std::env::temp_dir().join(format!(
+            "score_visualizer-vis-{}-{}",
+            std::process::id(),
+            base_name
+        ));
+        if let Err(e) = fs::create_dir_all(&dir) {
+            log!("Error creating {}: {}", dir.display(), e);
+            return result;
+        }
+        Some(dir)
+    };
+    let _turn = if !writes_vis_output && working_dir.is_some() {
+        Some(SHARED_WORKING_DIR.lock().unwrap_or_else(|e| e.into_inner()))
+    } else {
+        None
+    };
+    let run_dir = match scratch_dir {
+        Some(ref dir) => dir.clone(),
+        None => PathBuf::from(working_dir.unwrap_or(".")),
+    };
+
+    let mut program = PathBuf::from(&parts[0]);
+    let mut args: Vec<PathBuf> = parts[1..].iter().map(PathBuf::from).collect();
+    if scratch_dir.is_some() {
+        // A relative program path, or file named as an argument, still
+        // refers to the current directory
+        if program.components().count() > 1 {
+            program = std::path::absolute(&program).unwrap_or(program);
+        }
+        for arg in &mut args {
+            if arg.is_relative() && arg.exists() {
+                *arg = std::path::absolute(&*arg).unwrap_or_else(|_| arg.clone());
+            }
+        }
+    }
+    if writes_vis_output {
+        // A stale visualization from an earlier run must not pass for this one
+        let _ = fs::remove_file(&visualizer_file);
+    }
+    let mut cmd = Command::new(program);
+    cmd.args(&args);
+    if scratch_dir.is_some() || working_dir.is_some() {
+        cmd.current_dir(&run_dir);
+    }
+    let output = cmd.output();
+    result = collect_output(
+        result,
+        output,
+        writes_vis_output,
+        &run_dir,
+        &visualizer_file,
+        vis_config,
+        &base_name,
+    );
+    if let Some(ref dir) = scratch_dir {
+        let _ = fs::remove_dir_all(dir);
+    }
+    if !result.visualizer.is_empty() && vis_config.is_some_and(|v| v.thumbnails) {
+        result = snapshot(result, &abs_vis_output, vis_config.unwrap(), &base_name);
+    }
+    result
+}
+
+/// Captures a PNG of the visualization next to it and links it from `result`.
+fn snapshot(
+    mut result: Result,
+    vis_file: &Path,
+    vis_config: &VisualizerConfig,
+    base_name: &str,
+) -> Result {
+    let thumbnail_file = vis_file.with_extension("png");
+    let template = vis_config
+        .thumbnail_command
+        .as_deref()
+        .unwrap_or(DEFAULT_THUMBNAIL_COMMAND);
+    let Some(parts) = command_args(
+        template,
+        &[
+            ("vis_file", &vis_file.to_string_lossy()),
+            ("thumbnail_file", &thumbnail_file.to_string_lossy()),
+        ],
+    ) else {
+        return result;
+    };
+    // A stale snapshot from an earlier run must not pass for this one
+    let _ = fs::remove_file(&thumbnail_file);
+    match Command::new(&parts[0]).args(&parts[1..]).output() {
+        Ok(out) if out.status.success() && thumbnail_file.exists() => {
+            result.thumbnail = format!("visualizations/{}", base_name.replace(".txt", ".png"));
+        }
+        Ok(out) => log!(
+            "Error capturing thumbnail for {}: {}",
+            base_name,
+            String::from_utf8_lossy(&out.stderr)
+        ),
+        Err(e) => log!("Error running {}: {}", parts[0], e),
+    }
+    result
+}
+
+/// Moves the visualizer's HTML into place and links it from `result`.
+fn collect_output(
+    mut result: Result,
+    output: io::Result<Output>,
+    writes_vis_output: bool,
+    run_dir: &Path,
+    visualizer_file: &str,
+    vis_config: Option<&VisualizerConfig>,
+    base_name: &str,
+) -> Result {
+    if let Ok(out) = output {
+        if !out.status.success() {
+            log!(
+                "Error running visualizer for {}: {}",
+                base_name,
+                String::from_utf8_lossy(&out.stderr)
+            );
+            return result;
+        }
+        let vis_link = format!("visualizations/{}", base_name.replace(".txt", ".html"));
+        // The visualizer was told where to write
+        if writes_vis_output {
+            if Path::new(visualizer_file).exists() {
+                result.visualizer = vis_link;
+            } else {
+                log!("Visualizer did not write {}", visualizer_file);
+            }
+            return result;
+        }
+        // Otherwise it writes a fixed file name in its working directory
+        let vis_name = vis_config
+            .and_then(|v| v.output.as_deref())
+            .unwrap_or(DEFAULT_VIS_OUTPUT);
+        let vis_html = run_dir.join(vis_name);
+        if vis_html.exists() {
+            if let Err(_e) = fs::rename(&vis_html, visualizer_file) {
+                // rename may fail across filesystems, fall back to copy+remove
+                if let Err(e) = fs::copy(&vis_html, visualizer_file) {
+                    log!("Error copying {}: {}", vis_html.display(), e);
+                    return result;
+                }
+                let _ = fs::remove_file(&vis_html);
+            }
+            result.visualizer = vis_link;
+        } else {
+            // Check if the visualizer wrote stdout as HTML instead
+            let stdout_str = String::from_utf8_lossy(&out.stdout);
+            if !stdout_str.is_empty() {
+                if let Err(e) = fs::write(visualizer_file, stdout_str.as_bytes()) {
+                    log!("Error writing visualizer output: {}", e);
+                    return result;
+                }
+                result.visualizer = vis_link;
+            }
+        }
+    } else {
+        // Visualizer binary not found or failed to start - skip silently
+    }
+
+    result
+}